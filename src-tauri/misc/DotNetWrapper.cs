@@ -31,6 +31,10 @@ class Program
                 EvflTextToBinary();
                 break;
 
+            case "serve":
+                Serve();
+                break;
+
             default:
                 Console.WriteLine("Error: Unknown command. Use 'to-json' or 'to-binary'.");
                 break;
@@ -96,6 +100,75 @@ class Program
         }
     }
 
+    // Worker mode, same framing as totkbits.py serve (little endian):
+    //   request:  u32 command length, command, u32 payload length, payload
+    //   response: u8 status (0 ok, 1 error), u32 payload length, payload (json {command, type, message} on error)
+    static void Serve()
+    {
+        using (var input = new BinaryReader(Console.OpenStandardInput()))
+        using (var output = new BinaryWriter(Console.OpenStandardOutput()))
+        {
+            while (true)
+            {
+                byte[] commandBytes = ReadFrame(input);
+                byte[] payload = commandBytes == null ? null : ReadFrame(input);
+                if (payload == null)
+                {
+                    return; // parent closed the pipe
+                }
+                string command = Encoding.UTF8.GetString(commandBytes);
+                byte status = 0;
+                byte[] reply;
+                try
+                {
+                    switch (command)
+                    {
+                        case "ping":
+                            reply = Encoding.UTF8.GetBytes("pong");
+                            break;
+                        case "EvflBinaryToText":
+                            reply = Encoding.UTF8.GetBytes(BfevFile.FromBinary(payload).ToJson(format: true));
+                            break;
+                        case "EvflTextToBinary":
+                            reply = BfevFile.FromJson(Encoding.UTF8.GetString(payload)).ToBinary();
+                            break;
+                        default:
+                            throw new ArgumentException($"Command '{command}' not recognized.");
+                    }
+                }
+                catch (Exception e)
+                {
+                    status = 1;
+                    var error = new Dictionary<string, string>
+                    {
+                        { "command", command },
+                        { "type", e.GetType().Name },
+                        { "message", e.Message },
+                    };
+                    reply = Encoding.UTF8.GetBytes(JsonConvert.SerializeObject(error));
+                }
+                output.Write(status);
+                output.Write((uint)reply.Length);
+                output.Write(reply);
+                output.Flush();
+            }
+        }
+    }
+
+    static byte[] ReadFrame(BinaryReader input)
+    {
+        try
+        {
+            uint size = input.ReadUInt32();
+            byte[] data = input.ReadBytes((int)size);
+            return data.Length == size ? data : null;
+        }
+        catch (EndOfStreamException)
+        {
+            return null;
+        }
+    }
+
     static string ConvertJsonToYaml(string jsonString)
     {
         // Deserialize JSON to a .NET object (Dictionary or dynamic)
@@ -0,0 +1,314 @@
+use std::fs;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::Settings::makedirs;
+use crate::Zstd::{TotkFileType, TotkZstd, ZsDicType};
+
+// Compress or decompress a whole folder (usually a mod romfs) on all cores.
+// The output mirrors the source tree inside `dest`, which may be the source folder itself (originals are kept).
+//   Compress:   files without .zs become `<name>.zs`, dictionary and level from the compression rules
+//   Decompress: `.zs` files lose the extension, dictionary from the frame header, then from the path
+// Files that are not processed are copied when `dest` is another folder.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BatchMode {
+    Compress,
+    Decompress,
+}
+
+impl BatchMode {
+    pub fn from_str(mode: &str) -> Option<BatchMode> {
+        match mode.to_ascii_lowercase().as_str() {
+            "compress" => Some(BatchMode::Compress),
+            "decompress" => Some(BatchMode::Decompress),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub done: usize,
+    pub total: usize,
+    pub path: String, //relative to the source folder
+}
+
+// About 100 progress events for a whole folder is plenty for the status bar
+pub fn is_progress_step(progress: &BatchProgress) -> bool {
+    let step = (progress.total / 100).max(1);
+    progress.done % step == 0 || progress.done == progress.total
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+// Files a folder wide operation (batch, minimize, romfs index) failed on, serialized as a plain list
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct Failures(pub Vec<BatchFailure>);
+
+impl Failures {
+    pub fn push_error(&mut self, path: &str, error: &io::Error) {
+        self.0.push(BatchFailure { path: path.to_string(), error: error.to_string() });
+    }
+
+    // One line per file, for dialogs
+    pub fn report(&self, max_lines: usize) -> String {
+        let mut lines: Vec<String> = self
+            .0
+            .iter()
+            .take(max_lines)
+            .map(|f| format!("{}: {}", f.path, f.error))
+            .collect();
+        if self.0.len() > max_lines {
+            lines.push(format!("... and {} more", self.0.len() - max_lines));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Deref for Failures {
+    type Target = Vec<BatchFailure>;
+    fn deref(&self) -> &Vec<BatchFailure> {
+        &self.0
+    }
+}
+
+impl DerefMut for Failures {
+    fn deref_mut(&mut self) -> &mut Vec<BatchFailure> {
+        &mut self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a Failures {
+    type Item = &'a BatchFailure;
+    type IntoIter = std::slice::Iter<'a, BatchFailure>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchReport {
+    pub processed: usize,
+    pub copied: usize,
+    pub skipped: usize,
+    pub failures: Failures,
+}
+
+impl BatchReport {
+    pub fn summary(&self, mode: BatchMode) -> String {
+        let verb = match mode {
+            BatchMode::Compress => "Compressed",
+            BatchMode::Decompress => "Decompressed",
+        };
+        let mut res = format!("{} {} files", verb, self.processed);
+        if self.copied > 0 {
+            res.push_str(&format!(", copied {}", self.copied));
+        }
+        if self.skipped > 0 {
+            res.push_str(&format!(", skipped {}", self.skipped));
+        }
+        if !self.failures.is_empty() {
+            res.push_str(&format!(", {} failed", self.failures.len()));
+        }
+        res
+    }
+}
+
+enum FileResult {
+    Processed,
+    Copied,
+    Skipped,
+}
+
+fn compress(zstd: &TotkZstd, data: &Vec<u8>, rel_path: &str) -> io::Result<Vec<u8>> {
+    zstd.compress_file(data, rel_path, TotkFileType::Other, None)
+}
+
+// Dictionary from the frame header, then the one the compression rules give for the path, then the rest
+fn decompress(zstd: &TotkZstd, data: &Vec<u8>, rel_path: &str) -> io::Result<Vec<u8>> {
+    let from_path = zstd.compression.resolve(rel_path, TotkFileType::Other, None).dict;
+    let mut dicts: Vec<ZsDicType> = zstd.detect_dict(data).into_iter().collect();
+    for dict in [from_path, ZsDicType::Zs, ZsDicType::Bcett, ZsDicType::Pack, ZsDicType::Empty] {
+        if !dicts.contains(&dict) {
+            dicts.push(dict);
+        }
+    }
+    for dict in dicts {
+        if let Ok(dec_data) = zstd.decompress_with_dict(data, dict) {
+            return Ok(dec_data);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "Unable to decompress with any dictionary"))
+}
+
+fn process_file(zstd: &TotkZstd, mode: BatchMode, src: &Path, rel_path: &str, dest: &Path, in_place: bool) -> io::Result<FileResult> {
+    let is_zs = rel_path.to_ascii_lowercase().ends_with(".zs");
+    let out_rel = match (mode, is_zs) {
+        (BatchMode::Compress, false) => format!("{}.zs", rel_path),
+        (BatchMode::Decompress, true) => rel_path[..rel_path.len() - 3].to_string(),
+        _ => {
+            if in_place {
+                return Ok(FileResult::Skipped);
+            }
+            let out_path = dest.join(rel_path);
+            makedirs(&out_path)?;
+            fs::copy(src, &out_path)?;
+            return Ok(FileResult::Copied);
+        }
+    };
+    let data = fs::read(src)?;
+    let out_data = match mode {
+        BatchMode::Compress => compress(zstd, &data, rel_path)?,
+        BatchMode::Decompress => decompress(zstd, &data, rel_path)?,
+    };
+    let out_path = dest.join(out_rel);
+    makedirs(&out_path)?;
+    fs::write(&out_path, out_data)?;
+    Ok(FileResult::Processed)
+}
+
+// Relative paths use `/`, sorted so the order of processing (and failures) is stable
+pub fn collect_files(src: &Path) -> Vec<(PathBuf, String)> {
+    let mut files: Vec<(PathBuf, String)> = WalkDir::new(src)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel_path = e.path().strip_prefix(src).ok()?.to_string_lossy().replace("\\", "/");
+            Some((e.path().to_path_buf(), rel_path))
+        })
+        .collect();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    files
+}
+
+pub fn worker_count(total: usize) -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(total.max(1))
+}
+
+// Runs `process` for every (path, relative path) on all cores, `on_progress` after each file.
+// Workers stop taking files once `cancel` is set. Returns how many files were processed
+pub fn for_each_file_parallel<F, P>(files: &[(PathBuf, String)], cancel: Option<&AtomicBool>, process: F, on_progress: P) -> usize
+where
+    F: Fn(&Path, &str) + Sync,
+    P: Fn(&BatchProgress) + Sync,
+{
+    let total = files.len();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..worker_count(total) {
+            scope.spawn(|| loop {
+                if cancel.is_some_and(|cancel| cancel.load(Ordering::SeqCst)) {
+                    break;
+                }
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let (path, rel_path) = match files.get(i) {
+                    Some(file) => file,
+                    None => break,
+                };
+                process(path, rel_path);
+                let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                on_progress(&BatchProgress { done, total, path: rel_path.clone() });
+            });
+        }
+    });
+    done.into_inner()
+}
+
+pub fn batch_zstd_folder<F>(zstd: &TotkZstd, src: &Path, dest: &Path, mode: BatchMode, on_progress: F) -> io::Result<BatchReport>
+where
+    F: Fn(&BatchProgress) + Sync,
+{
+    if !src.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Folder not found: {:?}", src)));
+    }
+    if !zstd.has_zstd() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No romfs or ZsDic path found, zstd unavailable"));
+    }
+    let in_place = src == dest || src.canonicalize().ok() == dest.canonicalize().ok();
+    let files = collect_files(src);
+    println!("[+] {:?} {} files from {:?} on {} threads", mode, files.len(), src, worker_count(files.len()));
+
+    let report = Mutex::new(BatchReport::default());
+    let process = |path: &Path, rel_path: &str| {
+        let result = process_file(zstd, mode, path, rel_path, dest, in_place);
+        if let Ok(mut report) = report.lock() {
+            match result {
+                Ok(FileResult::Processed) => report.processed += 1,
+                Ok(FileResult::Copied) => report.copied += 1,
+                Ok(FileResult::Skipped) => report.skipped += 1,
+                Err(e) => report.failures.push_error(rel_path, &e),
+            }
+        }
+    };
+    for_each_file_parallel(&files, None, process, on_progress);
+
+    let mut report = report.into_inner().unwrap_or_else(|e| e.into_inner());
+    report.failures.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_files(count: usize) -> Vec<(PathBuf, String)> {
+        (0..count).map(|i| (PathBuf::from(format!("/mod/{}", i)), i.to_string())).collect()
+    }
+
+    #[test]
+    fn every_file_once() {
+        let files = fake_files(257);
+        let seen = Mutex::new(Vec::new());
+        let progress = AtomicUsize::new(0);
+        let done = for_each_file_parallel(
+            &files,
+            None,
+            |_, rel_path| seen.lock().unwrap().push(rel_path.to_string()),
+            |p| {
+                assert_eq!(p.total, files.len());
+                progress.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort_by_key(|s| s.parse::<usize>().unwrap());
+        assert_eq!(done, files.len());
+        assert_eq!(progress.into_inner(), files.len());
+        assert_eq!(seen, files.iter().map(|f| f.1.clone()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cancel_stops_workers() {
+        let files = fake_files(1000);
+        let cancel = AtomicBool::new(false);
+        let done = for_each_file_parallel(&files, Some(&cancel), |_, _| cancel.store(true, Ordering::SeqCst), |_| {});
+        //each worker finishes the file it holds
+        assert!((1..=worker_count(files.len())).contains(&done));
+    }
+
+    #[test]
+    fn progress_steps() {
+        let steps = |total: usize| {
+            (1..=total)
+                .filter(|&done| is_progress_step(&BatchProgress { done, total, path: String::new() }))
+                .count()
+        };
+        assert_eq!(steps(50), 50);
+        assert_eq!(steps(1000), 100);
+        assert_eq!(steps(1050), 105);
+    }
+}
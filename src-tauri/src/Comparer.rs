@@ -1,7 +1,7 @@
 use std::{io, path::Path, sync::Arc};
 
 use crate::{
-    file_format::{BinTextFile::OpenedFile, Pack::PackComparer}, Open_and_Save::{file_from_disk_to_senddata, get_string_from_data, SendData}, Settings::Pathlib, TotkApp::InternalFile, Zstd::TotkZstd
+    file_format::{BinTextFile::OpenedFile, Pack::PackComparer}, Interaction::UserInteraction, Open_and_Save::{file_from_disk_to_senddata, get_string_from_data, SendData}, Settings::Pathlib, TotkApp::InternalFile, Zstd::TotkZstd
 };
 //USELESS as of now, doesnt work
 #[derive(Debug)]
@@ -106,11 +106,10 @@ impl FileToCompare {
         Self { path: Pathlib::new(&path), label: String::new(), text: text, is_internal: false}
     }
 
-    pub fn get_path_from_dialog(&mut self, title: Option<String>) {
+    pub fn get_path_from_dialog(&mut self, title: Option<String>, ui: &dyn UserInteraction) {
         let title_to_set = title.unwrap_or("Select file".to_string());
-        let file = rfd::FileDialog::new()
-            .set_title(title_to_set)
-            .pick_file()
+        let file = ui
+            .pick_file(&title_to_set, &Vec::new())
             .unwrap_or_default()
             .to_string_lossy()
             .replace("\\", "//");
@@ -120,8 +119,12 @@ impl FileToCompare {
     }
     pub fn get_text_from_file_on_disk(&mut self, zstd: Arc<TotkZstd>) -> io::Result<()> {
         let file_res = file_from_disk_to_senddata(&self.path.full_path, zstd);
-        if let Some(file_res) = file_res {
-            self.text = file_res.1.text;
+        if let Some((opened_file, data)) = file_res {
+            //RSTB opens in its own tab without text, compare the whole table as yaml
+            self.text = match &opened_file.restbl {
+                Some(restbl) => restbl.to_text()?,
+                None => data.text,
+            };
         }
         Ok(())
     }
@@ -137,7 +140,7 @@ impl FileToCompare {
     }
 
     pub fn from_disk(&mut self, title: Option<String>, zstd: Arc<TotkZstd>) -> io::Result<()> {
-        self.get_path_from_dialog(title);
+        self.get_path_from_dialog(title, zstd.ui.as_ref());
         if !self.path.full_path.is_empty() {
             self.get_text_from_file_on_disk(zstd.clone())?;
             if !self.text.is_empty() {
@@ -182,11 +185,8 @@ impl DiffComparer {
         Self { file1: FileToCompare::default(), file2: FileToCompare::default() , MAX_COMPARE_SIZE}
     }
 
-    pub fn msgbox_max_size_exceeded(&self, size:usize) {
-        rfd::MessageDialog::new()
-            .set_title("Error")
-            .set_description(format!("File  too large to compare\nFile text size: {:.2} MB, max size: {} MB", (size as f64) /1024.0/1024.0, self.MAX_COMPARE_SIZE/1024/1024))
-            .show();
+    pub fn msgbox_max_size_exceeded(&self, size:usize, ui: &dyn UserInteraction) {
+        ui.message("Error", &format!("File  too large to compare\nFile text size: {:.2} MB, max size: {} MB", (size as f64) /1024.0/1024.0, self.MAX_COMPARE_SIZE/1024/1024));
     }
 
     pub fn files_from_disk(zstd: Arc<TotkZstd>, is_from_disk: bool) -> Option<SendData> {
@@ -214,7 +214,7 @@ impl DiffComparer {
         }
         let mut size = comp.file1.text.len();
         if size > comp.MAX_COMPARE_SIZE {
-            comp.msgbox_max_size_exceeded(size);
+            comp.msgbox_max_size_exceeded(size, zstd.ui.as_ref());
             return None;
         }
         //File2
@@ -226,10 +226,7 @@ impl DiffComparer {
             return None;
         }
         if comp.file2.path.full_path == comp.file1.path.full_path {
-            rfd::MessageDialog::new()
-                .set_title("Error")
-                .set_description(format!("Paths to both files are the same, skipping comparison\n{}", comp.file1.path.full_path))
-                .show();
+            zstd.ui.message("Error", &format!("Paths to both files are the same, skipping comparison\n{}", comp.file1.path.full_path));
             return None;
         }
         if comp.file2.text.is_empty() {
@@ -238,7 +235,7 @@ impl DiffComparer {
         }
         size = comp.file2.text.len();
         if size > comp.MAX_COMPARE_SIZE {
-            comp.msgbox_max_size_exceeded(size);
+            comp.msgbox_max_size_exceeded(size, zstd.ui.as_ref());
             return None;
         }
         
@@ -283,19 +280,13 @@ impl DiffComparer {
                 comp.file2.path = Pathlib::new(&vanila_path);
             },
             Err(err) => {
-                rfd::MessageDialog::new()
-                    .set_title("Error")
-                    .set_description(format!("ERROR:\n{:?}\nUnable to find original path for file:\n{:?}", &err, &path.as_ref()))
-                    .show();
+                zstd.ui.message("Error", &format!("ERROR:\n{:?}\nUnable to find original path for file:\n{:?}", &err, &path.as_ref()));
                 data.status_text = format!("ERROR: {:?}", err);
                 return Some(data);
             }
         }
         if comp.file2.path.full_path == comp.file1.path.full_path {
-            rfd::MessageDialog::new()
-                .set_title("Error")
-                .set_description(format!("Paths to both files are the same, skipping comparison\n{}", comp.file1.path.full_path))
-                .show();
+            zstd.ui.message("Error", &format!("Paths to both files are the same, skipping comparison\n{}", comp.file1.path.full_path));
             return None;
         }
 
@@ -304,10 +295,7 @@ impl DiffComparer {
             return Some(data);
         }
         if comp.file2.text.is_empty() {
-            rfd::MessageDialog::new()
-                .set_title("Error")
-                .set_description(format!("ERROR: Could not get text from original file: {}", &comp.file2.path.full_path))
-                .show();
+            zstd.ui.message("Error", &format!("ERROR: Could not get text from original file: {}", &comp.file2.path.full_path));
             data.status_text = format!("ERROR: failed to parse: {}", comp.file2.path.full_path);
             return Some(data);
         }
@@ -327,17 +315,14 @@ impl DiffComparer {
                 return Some(data);
             }
             if comp.file1.text.is_empty() {
-                rfd::MessageDialog::new()
-                    .set_title("Error")
-                    .set_description(format!("ERROR: Could not get text from internal file: {}", &comp.file1.path.full_path))
-                    .show();
+                zstd.ui.message("Error", &format!("ERROR: Could not get text from internal file: {}", &comp.file1.path.full_path));
                 data.status_text = format!("ERROR: failed to parse: {}", comp.file1.path.full_path);
                 return Some(data);
             }
         }
         comp.file1.is_internal = true;
         //File2
-        comp.file2.get_path_from_dialog(Some("Select second file to compare with".to_string()));
+        comp.file2.get_path_from_dialog(Some("Select second file to compare with".to_string()), zstd.ui.as_ref());
         if comp.file2.path.full_path.is_empty() {
             return None;
         }
@@ -354,10 +339,7 @@ impl DiffComparer {
             return Some(data);
         }
         if comp.file2.text.is_empty() {
-            rfd::MessageDialog::new()
-                .set_title("Error")
-                .set_description(format!("ERROR: Could not get text from file:\n{}", &comp.file2.path.full_path))
-                .show();
+            zstd.ui.message("Error", &format!("ERROR: Could not get text from file:\n{}", &comp.file2.path.full_path));
             data.status_text = format!("ERROR: failed to parse: {}", comp.file2.path.full_path);
             return Some(data);
         }
@@ -380,10 +362,7 @@ impl DiffComparer {
                 return Some(data);
             }
             if comp.file1.text.is_empty() {
-                rfd::MessageDialog::new()
-                .set_title("Error")
-                .set_description(format!("ERROR: Could not get text from internal file: {}", &comp.file1.path.full_path))
-                .show();
+                zstd.ui.message("Error", &format!("ERROR: Could not get text from internal file: {}", &comp.file1.path.full_path));
                 data.status_text = format!("ERROR: Failed to parse: {}", comp.file1.path.full_path);
                 return Some(data);
             }
@@ -396,10 +375,7 @@ impl DiffComparer {
                 comp.file2.text = text;
             },
             Err(err) => {
-                rfd::MessageDialog::new()
-                    .set_title("Error")
-                    .set_description(format!("ERROR:\n{:?}", &err))
-                    .show();
+                zstd.ui.message("Error", &format!("ERROR:\n{:?}", &err));
                 data.status_text = format!("ERROR: {:?}", err);
                 return Some(data);
             }
@@ -436,10 +412,7 @@ impl DiffComparer {
         };
         if let Some(x) = &res {
             if x.compare_data.file1.text == x.compare_data.file2.text {
-                rfd::MessageDialog::new()
-                    .set_title("Files are identical")
-                    .set_description(format!("Files:\n{}\nand\n{}\nare identical", &x.compare_data.file1.path.full_path, &x.compare_data.file2.path.full_path))
-                    .show();
+                zstd.ui.message("Files are identical", &format!("Files:\n{}\nand\n{}\nare identical", &x.compare_data.file1.path.full_path, &x.compare_data.file2.path.full_path));
                 return None;
             }
         }
@@ -0,0 +1,240 @@
+use std::io;
+
+use crate::Zstd::{TotkFileType, ZsDicType};
+
+// Which dictionary and zstd level every .zs save uses, configured in TotkConfig as
+//   "Compression level": default level (higher is smaller and slower)
+//   "Compression rules": `target=dictionary[:level]` separated by `;`, first match wins
+// Target is a file name glob (`*.pack`), a path glob if it contains `/` (`Mals/*`) or a file type in
+// brackets (`[Sarc]`). Dictionary is zs, pack, bcett, empty or auto (keep the detected one, zs for new files).
+// A dictionary detected when the file was opened always wins, so vanilla files round-trip unchanged.
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 16;
+pub const DEFAULT_COMPRESSION_RULES: &str =
+    "*.pack=pack; [Sarc]=pack; *.bcett.byml=bcett; resourcesizetable.product.*=empty; *=zs";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleTarget {
+    Glob(String),
+    FileType(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionRule {
+    pub target: RuleTarget,
+    pub dict: Option<ZsDicType>, //None: auto
+    pub level: Option<i32>,      //None: default level
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionChoice {
+    pub dict: ZsDicType,
+    pub level: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionPolicy {
+    pub default_level: i32,
+    pub rules: Vec<CompressionRule>,
+}
+
+impl Default for CompressionPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION_RULES, DEFAULT_COMPRESSION_LEVEL).unwrap_or(Self {
+            default_level: DEFAULT_COMPRESSION_LEVEL,
+            rules: Vec::new(),
+        })
+    }
+}
+
+impl CompressionPolicy {
+    pub fn new(rules: &str, default_level: i32) -> io::Result<CompressionPolicy> {
+        let default_level = check_level(default_level)?;
+        let mut res = Vec::new();
+        for rule in rules.split(|c| c == ';' || c == '\n') {
+            let rule = rule.trim();
+            if rule.is_empty() {
+                continue;
+            }
+            res.push(Self::parse_rule(rule)?);
+        }
+        Ok(CompressionPolicy { default_level, rules: res })
+    }
+
+    fn parse_rule(rule: &str) -> io::Result<CompressionRule> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        let (target, value) = rule
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("Compression rule without '=': {}", rule)))?;
+        let target = target.trim().to_ascii_lowercase().replace("\\", "/");
+        if target.is_empty() {
+            return Err(invalid(format!("Compression rule without target: {}", rule)));
+        }
+        let target = if target.starts_with('[') && target.ends_with(']') {
+            RuleTarget::FileType(target[1..target.len() - 1].trim().to_string())
+        } else {
+            RuleTarget::Glob(target)
+        };
+        let (dict, level) = match value.split_once(':') {
+            Some((dict, level)) => (dict.trim(), Some(level.trim())),
+            None => (value.trim(), None),
+        };
+        let dict = match dict.to_ascii_lowercase().as_str() {
+            "zs" => Some(ZsDicType::Zs),
+            "pack" => Some(ZsDicType::Pack),
+            "bcett" => Some(ZsDicType::Bcett),
+            "empty" | "none" => Some(ZsDicType::Empty),
+            "auto" | "" => None,
+            other => return Err(invalid(format!("Unknown dictionary \"{}\" in compression rule: {}", other, rule))),
+        };
+        let level = match level {
+            Some(level) => Some(check_level(
+                level
+                    .parse::<i32>()
+                    .map_err(|_| invalid(format!("Invalid level \"{}\" in compression rule: {}", level, rule)))?,
+            )?),
+            None => None,
+        };
+        Ok(CompressionRule { target, dict, level })
+    }
+
+    pub fn to_rules_string(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let target = match &rule.target {
+                    RuleTarget::Glob(glob) => glob.clone(),
+                    RuleTarget::FileType(file_type) => format!("[{}]", file_type),
+                };
+                let dict = rule.dict.map(|d| d.as_str()).unwrap_or("auto");
+                match rule.level {
+                    Some(level) => format!("{}={}:{}", target, dict, level),
+                    None => format!("{}={}", target, dict),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn find_rule(&self, path: &str, file_type: TotkFileType) -> Option<&CompressionRule> {
+        let mut path = path.to_ascii_lowercase().replace("\\", "/");
+        if path.ends_with(".zs") {
+            path.truncate(path.len() - 3);
+        }
+        let name = path.rsplit('/').next().unwrap_or_default().to_string();
+        let type_name = format!("{:?}", file_type).to_ascii_lowercase();
+        self.rules.iter().find(|rule| match &rule.target {
+            RuleTarget::FileType(t) => *t == type_name,
+            RuleTarget::Glob(glob) if glob.contains('/') => glob_match(glob, &path) || glob_match(&format!("*/{}", glob), &path),
+            RuleTarget::Glob(glob) => glob_match(glob, &name),
+        })
+    }
+
+    // Dictionary detected on open > rule dictionary > zs
+    pub fn resolve(&self, path: &str, file_type: TotkFileType, detected: Option<ZsDicType>) -> CompressionChoice {
+        let rule = self.find_rule(path, file_type);
+        let dict = detected
+            .or_else(|| rule.and_then(|r| r.dict))
+            .unwrap_or(ZsDicType::Zs);
+        let level = rule.and_then(|r| r.level).unwrap_or(self.default_level);
+        CompressionChoice { dict, level }
+    }
+}
+
+fn check_level(level: i32) -> io::Result<i32> {
+    let range = zstd::compression_level_range();
+    if !range.contains(&level) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Compression level {} out of range {}..={}", level, range.start(), range.end()),
+        ));
+    }
+    Ok(level)
+}
+
+// `*` matches any run of characters (including `/`), `?` a single one
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dict_for(path: &str, file_type: TotkFileType) -> ZsDicType {
+        CompressionPolicy::default().resolve(path, file_type, None).dict
+    }
+
+    #[test]
+    fn default_rules() {
+        assert_eq!(dict_for("Pack/Actor/DgnObj_Small.pack.zs", TotkFileType::Sarc), ZsDicType::Pack);
+        assert_eq!(dict_for("Foo/Bar.sarc.zs", TotkFileType::Sarc), ZsDicType::Pack);
+        assert_eq!(dict_for("Banc/MainField/Static.bcett.byml.zs", TotkFileType::Bcett), ZsDicType::Bcett);
+        assert_eq!(
+            dict_for("System/Resource/ResourceSizeTable.Product.121.rsizetable.zs", TotkFileType::Restbl),
+            ZsDicType::Empty
+        );
+        assert_eq!(dict_for("Mals/USen.Product.121.sarc.zs", TotkFileType::MalsSarc), ZsDicType::Zs);
+        assert_eq!(dict_for("Component/Foo.bgyml.zs", TotkFileType::Byml), ZsDicType::Zs);
+    }
+
+    #[test]
+    fn detected_dictionary_wins_over_rules() {
+        let policy = CompressionPolicy::new("*.pack=pack:19; *=zs", 16).unwrap();
+        let choice = policy.resolve("Actor.pack.zs", TotkFileType::Sarc, Some(ZsDicType::Zs));
+        assert_eq!(choice, CompressionChoice { dict: ZsDicType::Zs, level: 19 });
+        let choice = policy.resolve("Actor.pack.zs", TotkFileType::Sarc, None);
+        assert_eq!(choice, CompressionChoice { dict: ZsDicType::Pack, level: 19 });
+        let choice = policy.resolve("Table.rsizetable.zs", TotkFileType::Restbl, Some(ZsDicType::Empty));
+        assert_eq!(choice, CompressionChoice { dict: ZsDicType::Empty, level: 16 });
+    }
+
+    #[test]
+    fn auto_and_path_rules() {
+        let policy = CompressionPolicy::new("Mals/*=auto:3; [Byml]=bcett", 16).unwrap();
+        let choice = policy.resolve("romfs/Mals/USen.Product.121.sarc.zs", TotkFileType::MalsSarc, None);
+        assert_eq!(choice, CompressionChoice { dict: ZsDicType::Zs, level: 3 });
+        assert_eq!(policy.resolve("a.byml.zs", TotkFileType::Byml, None).dict, ZsDicType::Bcett);
+        assert_eq!(CompressionPolicy::new(&policy.to_rules_string(), 16).unwrap(), policy);
+    }
+
+    #[test]
+    fn invalid_rules_and_levels() {
+        assert!(CompressionPolicy::new("*=zs", 999).is_err());
+        assert!(CompressionPolicy::new("*=zs:999", 16).is_err());
+        assert!(CompressionPolicy::new("*=zs:abc", 16).is_err());
+        assert!(CompressionPolicy::new("*=lz4", 16).is_err());
+        assert!(CompressionPolicy::new("zs", 16).is_err());
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*.pack", "actor.pack"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a?c", "abc"));
+        assert!(glob_match("*/mals/*", "romfs/mals/usen.sarc"));
+        assert!(!glob_match("*.pack", "actor.pack.bak"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult};
+use totkbits_core::Interaction::{BaevChoice, FileFilters, UserInteraction};
+
+// UserInteraction for the Tauri app: every prompt from the core ends up as a native dialog
+#[derive(Debug, Clone, Default)]
+pub struct RfdDialogs;
+
+fn file_dialog(title: &str, filters: &FileFilters) -> FileDialog {
+    let mut dialog = FileDialog::new().set_title(title);
+    for (key, value) in filters {
+        dialog = dialog.add_filter(key, value);
+    }
+    dialog
+}
+
+impl UserInteraction for RfdDialogs {
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        MessageDialog::new()
+            .set_title(title)
+            .set_description(message)
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            == MessageDialogResult::Yes
+    }
+
+    fn message(&self, title: &str, message: &str) {
+        MessageDialog::new()
+            .set_title(title)
+            .set_description(message)
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    fn pick_file(&self, title: &str, filters: &FileFilters) -> Option<PathBuf> {
+        file_dialog(title, filters).pick_file()
+    }
+
+    fn pick_folder(&self, title: &str) -> Option<PathBuf> {
+        FileDialog::new().set_title(title).pick_folder()
+    }
+
+    fn save_file(&self, title: &str, file_name: &str, filters: &FileFilters) -> Option<PathBuf> {
+        file_dialog(title, filters)
+            .set_file_name(file_name)
+            .save_file()
+    }
+
+    fn choose_baev(&self) -> BaevChoice {
+        match MessageDialog::new()
+            .set_title("Select BAEV file")
+            .set_description("Would you like to select BAEV file manually? Select \"No\" to import data from romfs dump or \"Cancel\" if you want to skip baev data")
+            .set_buttons(MessageButtons::YesNoCancel)
+            .show()
+        {
+            MessageDialogResult::Yes => BaevChoice::Select,
+            MessageDialogResult::No => BaevChoice::FromRomfs,
+            _ => BaevChoice::Skip,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::file_format::Pack::PackComparer;
+use crate::Minimize::format_size;
+use crate::Open_and_Save::get_string_from_data;
+use crate::Zstd::{sha256, TotkFileType, TotkZstd};
+
+// Text artifact of PackComparer results, for reviewing mods: every added, modified and removed entry
+// with sizes and sha256. Modified BYML, AAMP and MSBT entries also get the keys added, removed or
+// changed, from both sides parsed to yaml and flattened to `path.to.key` (`[i]` for list items).
+const MAX_VALUE_LEN: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyChange {
+    pub key: String,
+    pub vanila: String,
+    pub modded: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SemanticDiff {
+    pub file_type: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<KeyChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDiff {
+    pub path: String,
+    pub status: EntryStatus,
+    pub size: Option<usize>,
+    pub vanila_size: Option<usize>,
+    pub hash: Option<String>,
+    pub vanila_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<SemanticDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PackDiffReport {
+    pub pack: String,
+    pub entries: Vec<EntryDiff>,
+}
+
+impl PackDiffReport {
+    pub fn from_pack(pack: &PackComparer, zstd: Arc<TotkZstd>) -> Self {
+        let mut report = PackDiffReport {
+            pack: pack.breadcrumbs().join(" > "),
+            entries: Vec::new(),
+        };
+        let opened = match &pack.opened {
+            Some(opened) => opened,
+            None => return report,
+        };
+        let modded: Vec<String> = pack.modded.keys().cloned().collect();
+        let removed: Vec<String> = pack.removed.iter().cloned().collect();
+        let vanila_data = pack.vanila_entries_data(&[modded.as_slice(), removed.as_slice()].concat());
+
+        for (path, hash) in pack.added.iter() {
+            report.entries.push(EntryDiff {
+                path: path.clone(),
+                status: EntryStatus::Added,
+                size: opened.sarc.get_data(path).map(|d| d.len()),
+                vanila_size: None,
+                hash: Some(hash.clone()),
+                vanila_hash: None,
+                semantic: None,
+            });
+        }
+        for (path, hash) in pack.modded.iter() {
+            let data = opened.sarc.get_data(path).map(|d| d.to_vec());
+            let vanila = vanila_data.get(path);
+            let semantic = match (&data, vanila) {
+                (Some(data), Some(vanila)) => semantic_diff(path, vanila.clone(), data.clone(), zstd.clone()),
+                _ => None,
+            };
+            report.entries.push(EntryDiff {
+                path: path.clone(),
+                status: EntryStatus::Modified,
+                size: data.as_ref().map(|d| d.len()),
+                vanila_size: vanila.map(|d| d.len()),
+                hash: Some(hash.clone()),
+                vanila_hash: vanila.map(|d| sha256(d.clone())).or_else(|| pack.global_sarc_data.get(path).cloned()),
+                semantic,
+            });
+        }
+        for path in removed.iter() {
+            let vanila = vanila_data.get(path);
+            report.entries.push(EntryDiff {
+                path: path.clone(),
+                status: EntryStatus::Removed,
+                size: None,
+                vanila_size: vanila.map(|d| d.len()),
+                hash: None,
+                vanila_hash: vanila.map(|d| sha256(d.clone())).or_else(|| pack.global_sarc_data.get(path).cloned()),
+                semantic: None,
+            });
+        }
+        report
+            .entries
+            .sort_by(|a, b| a.path.to_lowercase().cmp(&b.path.to_lowercase()));
+        report
+    }
+
+    fn count(&self, status: EntryStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} modified, {} removed",
+            self.count(EntryStatus::Added),
+            self.count(EntryStatus::Modified),
+            self.count(EntryStatus::Removed)
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut res = format!("# Diff of {}\n\n{}\n", self.pack, self.summary());
+        for (status, title) in [
+            (EntryStatus::Added, "Added"),
+            (EntryStatus::Modified, "Modified"),
+            (EntryStatus::Removed, "Removed"),
+        ] {
+            let entries: Vec<&EntryDiff> = self.entries.iter().filter(|e| e.status == status).collect();
+            if entries.is_empty() {
+                continue;
+            }
+            res.push_str(&format!("\n## {} ({})\n\n", title, entries.len()));
+            res.push_str("| Path | Size | Vanilla size | SHA-256 | Vanilla SHA-256 |\n|---|---|---|---|---|\n");
+            for e in entries.iter() {
+                res.push_str(&format!(
+                    "| `{}` | {} | {} | {} | {} |\n",
+                    e.path,
+                    e.size.map(format_size).unwrap_or_default(),
+                    e.vanila_size.map(format_size).unwrap_or_default(),
+                    short_hash(&e.hash),
+                    short_hash(&e.vanila_hash)
+                ));
+            }
+            for e in entries.iter() {
+                if let Some(semantic) = &e.semantic {
+                    res.push_str(&semantic_markdown(&e.path, semantic));
+                }
+            }
+        }
+        res
+    }
+}
+
+fn short_hash(hash: &Option<String>) -> String {
+    match hash {
+        Some(hash) => format!("`{}`", &hash[..hash.len().min(16)]),
+        None => String::new(),
+    }
+}
+
+fn semantic_markdown(path: &str, semantic: &SemanticDiff) -> String {
+    let mut res = format!(
+        "\n### `{}` [{}]\n\n{} keys added, {} removed, {} changed\n\n",
+        path,
+        semantic.file_type,
+        semantic.added.len(),
+        semantic.removed.len(),
+        semantic.changed.len()
+    );
+    for key in semantic.added.iter() {
+        res.push_str(&format!("- added `{}`\n", key));
+    }
+    for key in semantic.removed.iter() {
+        res.push_str(&format!("- removed `{}`\n", key));
+    }
+    for change in semantic.changed.iter() {
+        res.push_str(&format!("- changed `{}`: `{}` -> `{}`\n", change.key, change.vanila, change.modded));
+    }
+    res
+}
+
+// Both sides through get_string_from_data, only for formats with yaml text
+pub fn semantic_diff(path: &str, vanila: Vec<u8>, modded: Vec<u8>, zstd: Arc<TotkZstd>) -> Option<SemanticDiff> {
+    let (vanila_file, vanila_text) = get_string_from_data(path, vanila, zstd.clone())?;
+    let (modded_file, modded_text) = get_string_from_data(path, modded, zstd.clone())?;
+    let file_type = modded_file.file_type;
+    if vanila_file.file_type != file_type
+        || !matches!(file_type, TotkFileType::Byml | TotkFileType::Bcett | TotkFileType::Aamp | TotkFileType::Msbt)
+    {
+        return None;
+    }
+    let vanila_keys = flatten_yaml(&vanila_text)?;
+    let modded_keys = flatten_yaml(&modded_text)?;
+    let mut res = SemanticDiff {
+        file_type: format!("{:?}", file_type),
+        ..Default::default()
+    };
+    for (key, value) in modded_keys.iter() {
+        match vanila_keys.get(key) {
+            None => res.added.push(key.clone()),
+            Some(vanila_value) if vanila_value != value => res.changed.push(KeyChange {
+                key: key.clone(),
+                vanila: shorten(vanila_value),
+                modded: shorten(value),
+            }),
+            _ => {}
+        }
+    }
+    res.removed = vanila_keys
+        .keys()
+        .filter(|key| !modded_keys.contains_key(*key))
+        .cloned()
+        .collect();
+    Some(res)
+}
+
+fn shorten(value: &str) -> String {
+    if value.chars().count() <= MAX_VALUE_LEN {
+        return value.to_string();
+    }
+    format!("{}...", value.chars().take(MAX_VALUE_LEN).collect::<String>())
+}
+
+fn flatten_yaml(text: &str) -> Option<BTreeMap<String, String>> {
+    let value: Value = serde_yaml::from_str(text).ok()?;
+    let mut res = BTreeMap::new();
+    flatten_value(&value, String::new(), &mut res);
+    Some(res)
+}
+
+// Leaves only, tags (!u, !obj, ...) are kept in the value so a type change shows as a change
+fn flatten_value(value: &Value, key: String, res: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Mapping(map) => {
+            for (k, v) in map.iter() {
+                let name = match k {
+                    Value::String(s) => s.clone(),
+                    other => scalar_to_string(other),
+                };
+                let child = if key.is_empty() { name } else { format!("{}.{}", key, name) };
+                flatten_value(v, child, res);
+            }
+        }
+        Value::Sequence(seq) => {
+            for (i, v) in seq.iter().enumerate() {
+                flatten_value(v, format!("{}[{}]", key, i), res);
+            }
+        }
+        Value::Tagged(tagged) => match &tagged.value {
+            Value::Mapping(_) | Value::Sequence(_) => flatten_value(&tagged.value, key, res),
+            scalar => {
+                res.insert(key, format!("{} {}", tagged.tag, scalar_to_string(scalar)));
+            }
+        },
+        scalar => {
+            res.insert(key, scalar_to_string(scalar));
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::file_format::Pack::PackFile;
+use crate::Batch::{collect_files, for_each_file_parallel, worker_count, BatchProgress};
+use crate::Open_and_Save::get_string_from_data;
+use crate::RomfsIndexer::is_sarc_path;
+use crate::Zstd::{file_type_from_path, file_type_from_str, is_sarc, TotkFileType, TotkZstd};
+
+// Text search over a whole folder (mod romfs or the dump): every loose file and every sarc entry goes through
+// get_string_from_data and is matched line by line, NOT case sensitive like search_in_sarc.
+// Matches are handed over per file as they are found, `cancel` stops the workers after the file they are on.
+// The type filter works on file names, so unwanted files are not even parsed. Archives are always opened.
+const MAX_MATCHES: usize = 5000;
+const MAX_SNIPPET_LEN: usize = 160;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub path: String,      //relative to the searched folder
+    pub full_path: String, //file on disk, to open it
+    pub internal_path: String, //entry in the archive, empty for loose files
+    pub line: usize,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchReport {
+    pub query: String,
+    pub files: usize,
+    pub searched: usize,
+    pub matches: usize,
+    pub cancelled: bool,
+    pub truncated: bool,
+}
+
+impl SearchReport {
+    pub fn summary(&self) -> String {
+        let mut res = format!("Found {} matches for \"{}\" in {} files", self.matches, self.query, self.searched);
+        if self.truncated {
+            res.push_str(&format!(", stopped at {} matches", MAX_MATCHES));
+        } else if self.cancelled {
+            res.push_str(&format!(", cancelled after {} of {} files", self.searched, self.files));
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub query: String,
+    pub file_types: Vec<TotkFileType>, //empty: everything
+}
+
+impl SearchOptions {
+    // Types separated by `,` or spaces, names as in the cli (byml, bcett, aamp, msbt, ainb, asb, tag, evfl, xlink, text)
+    pub fn new(query: &str, file_types: &str) -> io::Result<SearchOptions> {
+        if query.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Empty search query"));
+        }
+        let mut types = Vec::new();
+        for name in file_types.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()) {
+            let file_type = file_type_from_str(name)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown file type: {}", name)))?;
+            types.push(file_type);
+        }
+        Ok(SearchOptions {
+            query: query.to_string(),
+            file_types: types,
+        })
+    }
+
+    fn accepts(&self, path: &str) -> bool {
+        if self.file_types.is_empty() {
+            return true;
+        }
+        let file_type = file_type_from_path(path).unwrap_or(TotkFileType::Text);
+        self.file_types.contains(&file_type)
+    }
+}
+
+fn shorten_line(line: &str, pos: usize) -> String {
+    let line = line.trim_end();
+    if line.len() <= MAX_SNIPPET_LEN {
+        return line.trim_start().to_string();
+    }
+    //pos is from the lowercase line, can be off for a few non ascii characters
+    let mut start = pos.saturating_sub(MAX_SNIPPET_LEN / 2).min(line.len());
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + MAX_SNIPPET_LEN).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}{}", if start > 0 { "..." } else { "" }, &line[start..end], if end < line.len() { "..." } else { "" })
+}
+
+fn find_in_text(text: &str, pattern: &str, base: &SearchMatch, res: &mut Vec<SearchMatch>) {
+    for (i, line) in text.lines().enumerate() {
+        if let Some(pos) = line.to_lowercase().find(pattern) {
+            res.push(SearchMatch {
+                line: i + 1,
+                snippet: shorten_line(line, pos),
+                ..base.clone()
+            });
+        }
+    }
+}
+
+fn search_file(zstd: Arc<TotkZstd>, path: &Path, rel_path: &str, options: &SearchOptions, pattern: &str) -> io::Result<Vec<SearchMatch>> {
+    let mut res = Vec::new();
+    let base = SearchMatch {
+        path: rel_path.to_string(),
+        full_path: path.to_string_lossy().replace("\\", "/"),
+        internal_path: String::new(),
+        line: 0,
+        snippet: String::new(),
+    };
+    if is_sarc_path(rel_path) {
+        let pack = PackFile::new(path, zstd.clone())?;
+        for file in pack.sarc.files() {
+            let name = file.name.unwrap_or_default();
+            if name.is_empty() || !options.accepts(name) {
+                continue;
+            }
+            let data = zstd.plain_data(file.data.to_vec());
+            if is_sarc(&data) {
+                continue; //nested archives are not searched
+            }
+            if let Some((_, text)) = get_string_from_data(name, data, zstd.clone()) {
+                let base = SearchMatch {
+                    internal_path: name.to_string(),
+                    ..base.clone()
+                };
+                find_in_text(&text, pattern, &base, &mut res);
+            }
+        }
+        return Ok(res);
+    }
+    if !options.accepts(rel_path) {
+        return Ok(res);
+    }
+    let data = zstd.plain_data(fs::read(path)?);
+    if let Some((_, text)) = get_string_from_data(rel_path, data, zstd.clone()) {
+        find_in_text(&text, pattern, &base, &mut res);
+    }
+    Ok(res)
+}
+
+pub fn search_folder<F, M>(
+    zstd: Arc<TotkZstd>,
+    folder: &Path,
+    options: &SearchOptions,
+    cancel: &AtomicBool,
+    on_progress: F,
+    on_matches: M,
+) -> io::Result<SearchReport>
+where
+    F: Fn(&BatchProgress) + Sync,
+    M: Fn(&[SearchMatch]) + Sync,
+{
+    if !folder.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Folder not found: {:?}", folder)));
+    }
+    let files = collect_files(folder);
+    let total = files.len();
+    let pattern = options.query.to_lowercase();
+    println!("[+] Searching \"{}\" in {} files from {:?} on {} threads", &options.query, total, folder, worker_count(total));
+
+    let report = Mutex::new(SearchReport {
+        query: options.query.clone(),
+        files: total,
+        ..Default::default()
+    });
+    let process = |path: &Path, rel_path: &str| match search_file(zstd.clone(), path, rel_path, options, &pattern) {
+        Ok(matches) if !matches.is_empty() => {
+            let mut report = report.lock().unwrap_or_else(|e| e.into_inner());
+            let room = MAX_MATCHES.saturating_sub(report.matches);
+            let matches = &matches[..matches.len().min(room)];
+            report.matches += matches.len();
+            if report.matches >= MAX_MATCHES {
+                report.truncated = true;
+                cancel.store(true, Ordering::SeqCst);
+            }
+            //under the lock, so the callers get the matches in the order they were counted
+            if !matches.is_empty() {
+                on_matches(matches);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => println!("[-] Unable to search {}: {:?}", rel_path, e),
+    };
+    let searched = for_each_file_parallel(&files, Some(cancel), process, on_progress);
+
+    let mut report = report.into_inner().unwrap_or_else(|e| e.into_inner());
+    report.searched = searched;
+    report.cancelled = report.searched < total;
+    Ok(report)
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+// Every prompt the core needs from the user goes through this trait, so the pack/format logic
+// never touches a windowing toolkit. The Tauri app answers with rfd dialogs, the cli and tests
+// use NonInteractive.
+pub type FileFilters = Vec<(String, Vec<String>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaevChoice {
+    Select,    //pick the baev file manually
+    FromRomfs, //import it from romfs dump
+    Skip,      //no baev data
+}
+
+pub trait UserInteraction: Send + Sync {
+    // Yes/No question, true means Yes
+    fn confirm(&self, title: &str, message: &str) -> bool;
+    // Error or info, only has an Ok button
+    fn message(&self, title: &str, message: &str);
+    fn pick_file(&self, title: &str, filters: &FileFilters) -> Option<PathBuf>;
+    fn pick_folder(&self, title: &str) -> Option<PathBuf>;
+    fn save_file(&self, title: &str, file_name: &str, filters: &FileFilters) -> Option<PathBuf>;
+    fn choose_baev(&self) -> BaevChoice;
+}
+
+// Never blocks: confirmations get `assume_yes`, pickers return None, messages go to stderr
+#[derive(Debug, Clone, Default)]
+pub struct NonInteractive {
+    pub assume_yes: bool,
+}
+
+impl NonInteractive {
+    pub fn new(assume_yes: bool) -> Self {
+        Self { assume_yes }
+    }
+}
+
+impl UserInteraction for NonInteractive {
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        eprintln!("[{}] {} -> {}", title, message, if self.assume_yes { "yes" } else { "no" });
+        self.assume_yes
+    }
+
+    fn message(&self, title: &str, message: &str) {
+        eprintln!("[{}] {}", title, message);
+    }
+
+    fn pick_file(&self, _title: &str, _filters: &FileFilters) -> Option<PathBuf> {
+        None
+    }
+
+    fn pick_folder(&self, _title: &str) -> Option<PathBuf> {
+        None
+    }
+
+    fn save_file(&self, _title: &str, _file_name: &str, _filters: &FileFilters) -> Option<PathBuf> {
+        None
+    }
+
+    fn choose_baev(&self) -> BaevChoice {
+        //nobody to pick a file, so take it from romfs if it's there
+        BaevChoice::FromRomfs
+    }
+}
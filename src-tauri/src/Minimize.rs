@@ -0,0 +1,215 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::Batch::Failures;
+use crate::TotkConfig::TotkConfig;
+use crate::Zstd::TotkZstd;
+
+// "Minimize mod": drop what the mod ships unchanged from vanilla.
+//   Pack:   entries with the same sha256 as vanilla (PackComparer::minimize)
+//   Folder: loose files identical to the romfs dump, compared decompressed so the zstd level does not matter
+// A dry run only lists what would go. The romfs dump itself is never minimized.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MinimizedEntry {
+    pub path: String,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MinimizeReport {
+    pub dry_run: bool,
+    pub entries: Vec<MinimizedEntry>,
+    pub bytes_saved: usize,
+    pub failures: Failures, //files that could not be compared or deleted
+}
+
+impl MinimizeReport {
+    pub fn new(dry_run: bool, mut entries: Vec<MinimizedEntry>) -> Self {
+        entries.sort_by(|a, b| a.path.to_lowercase().cmp(&b.path.to_lowercase()));
+        let bytes_saved = entries.iter().map(|e| e.size).sum();
+        Self {
+            dry_run,
+            entries,
+            bytes_saved,
+            failures: Failures::default(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let verb = if self.dry_run { "Would remove" } else { "Removed" };
+        let mut res = format!(
+            "{} {} files identical to vanilla, {} saved",
+            verb,
+            self.entries.len(),
+            format_size(self.bytes_saved)
+        );
+        if !self.failures.is_empty() {
+            res.push_str(&format!(", {} failed", self.failures.len()));
+        }
+        res
+    }
+
+    pub fn listing(&self, max_lines: usize) -> String {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .take(max_lines)
+            .map(|e| format!("{} ({})", e.path, format_size(e.size)))
+            .collect();
+        if self.entries.len() > max_lines {
+            lines.push(format!("... and {} more", self.entries.len() - max_lines));
+        }
+        lines.join("\n")
+    }
+}
+
+pub fn format_size(size: usize) -> String {
+    match size {
+        s if s >= 1 << 20 => format!("{:.2} MB", s as f64 / (1 << 20) as f64),
+        s if s >= 1 << 10 => format!("{:.2} KB", s as f64 / (1 << 10) as f64),
+        s => format!("{} B", s),
+    }
+}
+
+// Vanilla lookup is by file name, the folder must match as well: `<mod>/romfs/Pack/Actor/X.pack.zs` only
+// matches `<romfs>/Pack/Actor/X.pack.zs`
+fn romfs_dir(path: &str) -> String {
+    let path = path.replace("\\", "/").to_lowercase();
+    let path = match path.rfind("romfs/") {
+        Some(i) => path[i + "romfs/".len()..].to_string(),
+        None => path.trim_start_matches('/').to_string(),
+    };
+    path.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default()
+}
+
+fn is_same_as_vanila(zstd: &TotkZstd, path: &Path, vanila_path: &str) -> io::Result<bool> {
+    let data = fs::read(path)?;
+    let vanila_data = fs::read(vanila_path)?;
+    if data == vanila_data {
+        return Ok(true);
+    }
+    Ok(zstd.plain_data(data) == zstd.plain_data(vanila_data))
+}
+
+// Every file of a folder holding the romfs dump, or inside it, is "identical to vanilla"
+fn check_outside_romfs(folder: &Path, romfs: &str) -> io::Result<()> {
+    let folder = folder.canonicalize()?;
+    let romfs = Path::new(romfs).canonicalize()?;
+    if folder.starts_with(&romfs) || romfs.starts_with(&folder) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} overlaps the romfs dump {}, refusing to minimize it", folder.display(), romfs.display()),
+        ));
+    }
+    Ok(())
+}
+
+// Deletes (unless dry run) the files of `folder` identical to their romfs version
+pub fn minimize_folder(zstd: &TotkZstd, folder: &Path, dry_run: bool) -> io::Result<MinimizeReport> {
+    if !folder.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Folder not found: {:?}", folder)));
+    }
+    if !zstd.is_valid() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No valid romfs path in config, vanilla comparison unavailable"));
+    }
+    let config: &TotkConfig = &zstd.totk_config;
+    check_outside_romfs(folder, &config.romfs)?;
+    let filenames = config.get_filename_to_localpath_data()?;
+    let mut failures = Failures::default();
+    //everything is compared before the first file is deleted
+    let mut matches: Vec<(PathBuf, MinimizedEntry)> = Vec::new();
+    for entry in WalkDir::new(folder).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let rel_path = path
+            .strip_prefix(folder)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace("\\", "/");
+        let vanila_path = match config.find_vanila_file_in_romfs_with(&filenames, path) {
+            Ok(vanila_path) => vanila_path,
+            Err(_) => continue, //not a vanilla file
+        };
+        if romfs_dir(&rel_path) != romfs_dir(&vanila_path[config.romfs.len().min(vanila_path.len())..]) {
+            continue;
+        }
+        match is_same_as_vanila(zstd, path, &vanila_path) {
+            Ok(true) => {
+                let size = entry.metadata().map(|m| m.len() as usize).unwrap_or_default();
+                matches.push((path.to_path_buf(), MinimizedEntry { path: rel_path, size }));
+            }
+            Ok(false) => {}
+            Err(e) => failures.push_error(&rel_path, &e),
+        }
+    }
+    let mut entries: Vec<MinimizedEntry> = Vec::new();
+    for (path, entry) in matches {
+        if !dry_run {
+            if let Err(e) = fs::remove_file(&path) {
+                failures.push_error(&entry.path, &e);
+                continue;
+            }
+        }
+        entries.push(entry);
+    }
+    if !dry_run {
+        remove_empty_dirs(folder);
+    }
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut report = MinimizeReport::new(dry_run, entries);
+    report.failures = failures;
+    Ok(report)
+}
+
+// Folders left empty after the cleanup, deepest first. The folder itself stays
+fn remove_empty_dirs(folder: &Path) {
+    let dirs: Vec<_> = WalkDir::new(folder)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    for dir in dirs {
+        let _ = fs::remove_dir(&dir); //fails unless empty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_romfs_and_overlapping_folders() {
+        let root = std::env::temp_dir().join(format!("totkbits_minimize_{}", std::process::id()));
+        let romfs = root.join("dump").join("romfs");
+        let inside = romfs.join("Pack");
+        let mod_dir = root.join("mod").join("romfs");
+        for dir in [&inside, &mod_dir] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let romfs_str = romfs.to_string_lossy().to_string();
+        assert!(check_outside_romfs(&romfs, &romfs_str).is_err());
+        assert!(check_outside_romfs(&inside, &romfs_str).is_err());
+        assert!(check_outside_romfs(&root, &romfs_str).is_err());
+        assert!(check_outside_romfs(&mod_dir, &romfs_str).is_ok());
+        //same folder through another spelling
+        assert!(check_outside_romfs(&inside.join(".."), &romfs_str).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn romfs_dir_ignores_the_mod_prefix() {
+        assert_eq!(romfs_dir("mod/romfs/Pack/Actor/X.pack.zs"), "pack/actor");
+        assert_eq!(romfs_dir("/Pack/Actor/X.pack.zs"), "pack/actor");
+        assert_eq!(romfs_dir("X.pack.zs"), "");
+    }
+}
@@ -1,10 +1,9 @@
 use crate::{
     file_format::{
-        Ainb_py::Ainb_py, Asb_py::{Asb_py, ASB_SEPARATOR}, BinTextFile::{is_banc_path, replace_rotate_deg_to_rad, BymlFile, OpenedFile}, Esetb::Esetb, Evfl_cs::Evfl, Msbt::str_endian_to_roead, Pack::{PackComparer, PackFile, SarcPaths}, Rstb::Restbl, TagProduct::TagProduct, Wrapper::PythonWrapper, Xlink::Xlink_rs, SMO::SmoSaveFile::SmoSaveFile
-    }, Comparer::DiffComparer, Settings::Pathlib, TotkApp::InternalFile, Zstd::{is_aamp, is_ainb, is_ainb_path, is_asb_path, is_byml, is_byml_path, is_esetb_path, is_evfl_path, is_gamedatalist, is_msbt_path, is_msyt, is_rstb_path, is_tagproduct_path, is_xlink_path, TotkFileType, TotkZstd}
+        Ainb_py::Ainb_py, Asb_py::{Asb_py, ASB_SEPARATOR}, BinTextFile::{is_banc_path, replace_rotate_deg_to_rad, BymlFile, MsbtHandle, OpenedFile}, Esetb::Esetb, HexDump::{from_hex_dump, to_hex_dump}, Evfl_cs::Evfl, Msbt::str_endian_to_roead, Pack::{PackComparer, PackFile, SarcPaths}, Rstb::Restbl, TagProduct::TagProduct, Wrapper::PythonWrapper, Xlink::Xlink_rs, SMO::SmoSaveFile::SmoSaveFile
+    }, Comparer::DiffComparer, Interaction::{FileFilters, UserInteraction}, Settings::Pathlib, TotkApp::InternalFile, Zstd::{ZsDicType, is_aamp, is_zstd, is_ainb, is_ainb_path, is_asb_path, is_byml, is_byml_path, is_esetb_path, is_evfl_path, is_gamedatalist, is_msbt_path, is_msyt, is_rstb_path, is_tagproduct_path, is_xlink_path, TotkFileType, TotkZstd}
 };
 use msbt_bindings_rs::MsbtCpp::MsbtCpp;
-use rfd::{FileDialog, MessageDialog};
 use roead::{aamp::ParameterIO, byml::Byml};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -47,7 +46,7 @@ pub fn open_msbt<P:AsRef<Path>>(path: P) -> Option<(OpenedFile<'static>, SendDat
     let mut opened_file = OpenedFile::default();
     let mut data = SendData::default();
     print!("Is {} a msbt?", &file_name);
-    opened_file.msyt = MsbtCpp::from_binary_file(&file_name).ok();
+    opened_file.msyt = MsbtCpp::from_binary_file(&file_name).ok().map(MsbtHandle);
     if let Some(m) = &opened_file.msyt {
         // let m = opened_file.msyt.as_ref().unwrap();
         println!(" yes!");
@@ -112,6 +111,50 @@ pub fn open_aamp<P: AsRef<Path>>(path: P) -> Option<(OpenedFile<'static>, SendDa
     None
 }
 
+//Any .zs file: decompress with the detected dictionary and open the content by its magic,
+//unknown binary content is shown as hex dump. Saving recompresses with the same dictionary
+pub fn open_zs_container<P: AsRef<Path>>(path: P, zstd: Arc<TotkZstd>) -> Option<(OpenedFile, SendData)> {
+    let path_ref = path.as_ref();
+    let pathlib_var = Pathlib::new(path_ref);
+    print!("Is {} a zstd container? ", &pathlib_var.full_path);
+    let rawdata = fs::read(path_ref).ok()?;
+    if !is_zstd(&rawdata) {
+        println!(" no");
+        return None;
+    }
+    let (dec_data, dict) = match zstd.decompress_detect(&rawdata) {
+        Ok(res) => res,
+        Err(e) => {
+            println!(" no: {}", e);
+            return None;
+        }
+    };
+    let mut inner_path = pathlib_var.full_path.clone();
+    if inner_path.to_ascii_lowercase().ends_with(".zs") {
+        inner_path.truncate(inner_path.len() - 3);
+    }
+    let mut opened_file = OpenedFile::default();
+    let mut data = SendData::default();
+    let (file_type, endian, text) = match get_string_from_data(&inner_path, dec_data.clone(), zstd.clone()) {
+        Some((internal_file, text)) => {
+            opened_file.byml = internal_file.byml;
+            opened_file.esetb = internal_file.esetb;
+            (internal_file.file_type, internal_file.endian, text)
+        }
+        None => (TotkFileType::Hex, None, to_hex_dump(&dec_data)),
+    };
+    println!(" yes! [{:?}] [{} dictionary]", file_type, dict.as_str());
+    opened_file.path = pathlib_var.clone();
+    opened_file.file_type = file_type;
+    opened_file.endian = endian;
+    opened_file.zs_dict = Some(dict);
+    data.status_text = format!("Opened {} [{} dictionary]", &pathlib_var.full_path, dict.as_str());
+    data.path = pathlib_var;
+    data.text = text;
+    data.get_file_label(file_type, endian);
+    Some((opened_file, data))
+}
+
 pub fn get_string_from_data<P: AsRef<Path>>(
     filepath: P,
     data: Vec<u8>,
@@ -145,7 +188,7 @@ pub fn get_string_from_data<P: AsRef<Path>>(
     }
 
     if is_ainb(&data) {
-        if let Ok(text) = Ainb_py::new().binary_to_text(&data) {
+        if let Ok(text) = Ainb_py::from_config(&zstd.totk_config).binary_to_text(&data) {
             internal_file.endian = Some(roead::Endian::Little);
             internal_file.path = Pathlib::new(path.clone());
             internal_file.file_type = TotkFileType::AINB;
@@ -160,6 +203,7 @@ pub fn get_string_from_data<P: AsRef<Path>>(
                 internal_file.byml = Some(byml_file);
                 let byml_ref = internal_file.byml.as_ref().unwrap(); // Safe due to the line above
                 internal_file.endian = byml_ref.endian.clone();
+                internal_file.zs_dict = byml_ref.file_data.zs_dict;
                 internal_file.path = Pathlib::new(path);
                 internal_file.file_type = byml_ref.file_data.file_type.clone(); // Set file type
                 return Some((internal_file, text));
@@ -215,9 +259,9 @@ fn write_data_to_file<P: AsRef<Path>>(path: P, data: Vec<u8>) -> io::Result<()>
 }
 
 #[allow(dead_code)]
-pub fn save_file_dialog(file_name: Option<String>) -> String {
+pub fn save_file_dialog(file_name: Option<String>, ui: &dyn UserInteraction) -> String {
     let name = file_name.unwrap_or_default();
-    let file = FileDialog::new().set_file_name(name).save_file();
+    let file = ui.save_file("Save", &name, &Vec::new());
     match file {
         Some(res) => {
             return res.to_string_lossy().into_owned();
@@ -237,13 +281,7 @@ pub fn check_if_save_in_romfs(dest_file: &str, zstd: Arc<TotkZstd>) -> bool {
                 "About to save file:\n{}\nin romfs dump. Continue?",
                 &dest_file
             );
-            if MessageDialog::new()
-                .set_title("Warning")
-                .set_description(m)
-                .set_buttons(rfd::MessageButtons::YesNo)
-                .show()
-                == rfd::MessageDialogResult::Yes
-            {
+            if zstd.ui.confirm("Warning", &m) {
                 return true;
             }
         }
@@ -258,6 +296,7 @@ pub fn get_binary_by_filetype(
     zstd: Arc<TotkZstd>,
     file_path: &str,
     opened_file: &mut OpenedFile<'_>,
+    zs_dict: Option<ZsDicType>,
 ) -> Option<Vec<u8>> {
     let mut rawdata: Vec<u8> = Vec::new();
     let endian_str = match endian {
@@ -265,14 +304,13 @@ pub fn get_binary_by_filetype(
         roead::Endian::Little => "LE",
     };
     let is_zs = file_path.to_lowercase().ends_with(".zs");
-    let is_bcett = file_path.to_lowercase().ends_with(".bcett.byml.zs");
     match file_type {
         TotkFileType::Xlink => {
             let xlink = Xlink_rs::new(zstd.clone()).ok()?;
             if let Ok(new_data) = xlink.yaml_to_binary(text) {
                 rawdata = new_data;
                 if is_zs {
-                    rawdata = zstd.compress_zs(&rawdata).ok()?;
+                    rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
                 }
             }
         }
@@ -280,7 +318,7 @@ pub fn get_binary_by_filetype(
           let evfl = Evfl::new(zstd.clone());
           if let Ok(new_data) = evfl.string_to_binary(text) {
             if is_zs {
-                if let Ok(compressed_data) = zstd.compress_zs(&new_data) {
+                if let Ok(compressed_data) = zstd.compress_file(&new_data, file_path, file_type, zs_dict) {
                     rawdata = compressed_data;
                 }
             } else {
@@ -294,8 +332,18 @@ pub fn get_binary_by_filetype(
                 esetb.update_from_text(text).ok()?;
                 rawdata = esetb.to_binary();
                 if file_path.to_lowercase().ends_with(".zs") {
-                    rawdata = zstd.compress_zs(&rawdata).ok()?;
+                    rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
+                }
+            }
+        }
+        TotkFileType::Restbl => {
+            //the table edited as text, the opened one is updated too so the RSTB tab stays in sync
+            if let Some(restbl) = &mut opened_file.restbl {
+                if let Err(e) = restbl.update_from_text(text) {
+                    zstd.ui.message("Error", &format!("Unable to parse RSTB text:\n{}", e));
+                    return None;
                 }
+                rawdata = restbl.table.to_binary();
             }
         }
         TotkFileType::ASB => {
@@ -317,12 +365,12 @@ pub fn get_binary_by_filetype(
                 
 
                 if is_zs {
-                    rawdata = zstd.compress_zs(&rawdata).ok()?;
+                    rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
                 }
                 //save baev
                 if !baev_data.is_empty() {
                     let baev_path = Path::new(&p.parent).join(format!("{}.root.baev.zs", name));
-                    baev_data = zstd.compress_zs(&baev_data).ok()?;
+                    baev_data = zstd.compress_file(&baev_data, &baev_path, TotkFileType::Other, None).ok()?;
                     if let Ok(mut file) = File::create(&baev_path) {
                         if let Err(e) = file.write_all(&baev_data) {
                             println!("Error writing baev data: {}", e);
@@ -336,7 +384,7 @@ pub fn get_binary_by_filetype(
             }
         }
         TotkFileType::AINB => {
-            if let Ok(some_data) = Ainb_py::new().text_to_binary(text) {
+            if let Ok(some_data) = Ainb_py::from_config(&zstd.totk_config).text_to_binary(text) {
                 rawdata = some_data;
             }
         }
@@ -344,14 +392,14 @@ pub fn get_binary_by_filetype(
             if let Ok(some_data) = TagProduct::to_binary(text) {
                 rawdata = some_data;
                 if is_zs {
-                    rawdata = zstd.compress_zs(&rawdata).ok()?;
+                    rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
                 }
             }
         }
         TotkFileType::Byml => {
             if (is_gamedatalist(file_path)) {
                 println!("is_gamedatalist, attempting to use oead python");
-                let p_wrap = PythonWrapper::new();
+                let p_wrap = PythonWrapper::from_config(&zstd.totk_config);
                 match p_wrap.text_to_binary(&text.as_bytes().to_vec(), "byml_text_to_binary".to_string()) {
                     Ok(some_data) => {
                         rawdata = some_data;
@@ -372,12 +420,8 @@ pub fn get_binary_by_filetype(
                 let pio = Byml::from_text(processed_text).ok()?;
                 rawdata = pio.to_binary(endian);
             }
-            if (!rawdata.is_empty()) {
-                if is_bcett {
-                    rawdata = zstd.compress_bcett(&rawdata).ok()?;
-                } else if is_zs {
-                    rawdata = zstd.compress_zs(&rawdata).ok()?;
-                }
+            if (!rawdata.is_empty() && is_zs) {
+                rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
             }
         }
         TotkFileType::Bcett => {
@@ -385,7 +429,7 @@ pub fn get_binary_by_filetype(
             let pio = Byml::from_text(processed_text).ok()?;
             rawdata = pio.to_binary(endian);
             if is_zs {
-                rawdata = zstd.compress_bcett(&rawdata).ok()?;
+                rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
             }
         }
         TotkFileType::Msbt => {
@@ -406,8 +450,21 @@ pub fn get_binary_by_filetype(
         TotkFileType::Text => {
             rawdata = text.as_bytes().to_vec();
         }
+        TotkFileType::Hex => {
+            rawdata = match from_hex_dump(text) {
+                Ok(data) => data,
+                Err(e) => {
+                    zstd.ui.message("Error", &format!("Unable to parse hex dump:\n{}", e));
+                    return None;
+                }
+            };
+        }
         _ => {}
     }
+    //formats without their own compression step (msbt, aamp, text, hex...) inside .zs
+    if is_zs && !rawdata.is_empty() && !is_zstd(&rawdata) {
+        rawdata = zstd.compress_file(&rawdata, file_path, file_type, zs_dict).ok()?;
+    }
 
     Some(rawdata)
 }
@@ -519,19 +576,13 @@ impl SaveFileDialog<'_> {
         self.process_name();
     }
 
-    pub fn show(&mut self) -> String {
+    pub fn show(&mut self, ui: &dyn UserInteraction) -> String {
         // self.generate_filters();
         // self.process_name();
         let mut result = String::new();
-        let mut dialog = FileDialog::new()
-            .set_file_name(self.name.clone().unwrap_or_default())
-            .set_title(&self.title);
-        for (key, value) in &self.filters {
-            dialog = dialog.add_filter(key, value);
-        }
-        let file = dialog
-            .add_filter("All files", &vec!["*".to_string()])
-            .save_file();
+        let mut filters: FileFilters = self.filters.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        filters.push(("All files".to_string(), vec!["*".to_string()]));
+        let file = ui.save_file(&self.title, &self.name.clone().unwrap_or_default(), &filters);
         if let Some(res) = file {
             result = res.to_string_lossy().into_owned();
         }
@@ -584,6 +635,7 @@ impl SendData {
         }
     }
     pub fn get_sarc_paths(&mut self, pack: &PackComparer<'_>) {
+        self.sarc_paths.breadcrumbs = pack.breadcrumbs();
         if let Some(opened) = &pack.opened {
             for file in opened.sarc.files() {
                 if let Some(name) = file.name {
@@ -596,6 +648,10 @@ impl SendData {
             for (path, _) in pack.modded.iter() {
                 self.sarc_paths.modded_paths.push(path.into());
             }
+            self.sarc_paths.removed_paths = pack.removed.iter().cloned().collect();
+            self.sarc_paths
+                .removed_paths
+                .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
             self.sarc_paths
                 .paths
                 .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
@@ -672,11 +728,7 @@ pub fn file_from_disk_to_senddata<P: AsRef<Path>>(path: P, zstd: Arc<TotkZstd>)
                 .or_else(|| open_aamp(&file_name))
                 .or_else(|| Evfl::open_file(&file_name, zstd.clone()))
                 .or_else(|| SmoSaveFile::open_smo_save_file(&file_name, zstd.clone()))
-                .or_else(|| open_text(&file_name))
-                .map(|(opened_file, data)| {
-                    // self.opened_file = opened_file;
-                    // self.internal_file = None;
-                    (opened_file, data)
-                });
+                .or_else(|| open_zs_container(&file_name, zstd.clone()))
+                .or_else(|| open_text(&file_name));
     res
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::env;
+
+use libloading::{Library, Symbol};
+
+use crate::TotkConfig::TotkConfig;
+use crate::Zstd::get_executable_dir;
+
+// Native helpers (shared libraries in bin/dlls). Nothing is loaded until a format actually needs it,
+// so a missing helper only breaks that format instead of the whole app.
+pub const XLINK_PLUGIN: &str = "xlink_tool";
+
+pub struct CppDll {
+    pub path: String,
+    pub lib: Library
+}
+
+impl CppDll {
+    pub fn load(path: &str) -> io::Result<CppDll> {
+        if !Path::new(path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Native helper not found: {}", path),
+            ));
+        }
+        let lib = unsafe { Library::new(path) }.map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unable to load native helper (wrong platform or architecture?): {}\n{}", path, e),
+            )
+        })?;
+        Ok(CppDll { path: path.to_string(), lib })
+    }
+
+    pub fn get_function<T>(&self, func_name: &str) -> io::Result<Symbol<T>> {
+        unsafe {
+            let lib = &self.lib;
+            let mut func_name_bytes = func_name.as_bytes().to_vec();
+            func_name_bytes.push(0); // Null-terminate the string
+            match lib.get(func_name_bytes.as_slice()) {
+                Ok(func) => Ok(func),
+                Err(_) => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Function {} not found in {}, incompatible helper version?", func_name, &self.path),
+                )),
+            }
+        }
+    }
+}
+
+pub struct PluginRegistry {
+    //plugin name -> path from config, empty means default location
+    pub paths: HashMap<String, String>,
+    loaded: Mutex<HashMap<String, Arc<CppDll>>>,
+}
+
+impl Default for PluginRegistry {
+    fn default() -> Self {
+        Self {
+            paths: HashMap::new(),
+            loaded: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl PluginRegistry {
+    pub fn new(totk_config: &TotkConfig) -> Self {
+        let mut res = Self::default();
+        res.paths.insert(XLINK_PLUGIN.to_string(), totk_config.xlink_plugin_path.clone());
+        res
+    }
+
+    // bin/dlls/xlink_tool.dll, bin/dlls/libxlink_tool.so, bin/dlls/libxlink_tool.dylib
+    pub fn default_path(name: &str) -> String {
+        format!("{}/bin/dlls/{}{}{}", get_executable_dir(), env::consts::DLL_PREFIX, name, env::consts::DLL_SUFFIX)
+    }
+
+    pub fn path(&self, name: &str) -> String {
+        match self.paths.get(name) {
+            Some(p) if !p.is_empty() => p.replace("\\", "/"),
+            _ => Self::default_path(name),
+        }
+    }
+
+    // Loads on first use. Failures are not cached, so dropping the library in place works without a restart
+    pub fn get(&self, name: &str) -> io::Result<Arc<CppDll>> {
+        let mut loaded = self.loaded.lock().map_err(|_| io::Error::new(io::ErrorKind::Other, "Plugin registry poisoned"))?;
+        if let Some(dll) = loaded.get(name) {
+            return Ok(dll.clone());
+        }
+        let dll = Arc::new(CppDll::load(&self.path(name))?);
+        println!("[+] Loaded native helper: {}", &dll.path);
+        loaded.insert(name.to_string(), dll.clone());
+        Ok(dll)
+    }
+
+    pub fn xlink(&self) -> io::Result<Arc<CppDll>> {
+        self.get(XLINK_PLUGIN)
+    }
+}
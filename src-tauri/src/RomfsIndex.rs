@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+use flate2::read::ZlibDecoder;
+
+use crate::RomfsIndexer::{read_index_db, FILENAME_TO_LOCALPATH_DB, INTERNAL_FILEPATHS_DB, SARC_SHA256_DB};
+use crate::TotkConfig::TotkConfig;
+
+// In memory copy of the lookup databases, each one read on first use and shared afterwards (the config
+// is behind an Arc, clones of it share the cache too). Keyed by the resolved path, so a database indexed
+// for the dump (bin/index/<version>/) and the prebuilt one never mix.
+// Failures are not cached, indexing the romfs takes effect after `clear` without a restart.
+pub const RSTB_PATHS_DB: &str = "bin/totk_rstb_paths.bin";
+
+#[derive(Default)]
+pub struct RomfsIndex {
+    maps: Mutex<HashMap<String, Arc<HashMap<String, String>>>>,
+    rstb_paths: Mutex<Option<Arc<Vec<String>>>>,
+}
+
+impl fmt::Debug for RomfsIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let loaded: Vec<String> = self.maps.lock().map(|m| m.keys().cloned().collect()).unwrap_or_default();
+        f.debug_struct("RomfsIndex").field("loaded", &loaded).finish()
+    }
+}
+
+fn poisoned() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "Romfs index poisoned")
+}
+
+impl RomfsIndex {
+    pub fn get(&self, config: &TotkConfig, name: &str) -> io::Result<Arc<HashMap<String, String>>> {
+        let path = config.get_index_db_path(name).to_string_lossy().replace("\\", "/");
+        let mut maps = self.maps.lock().map_err(|_| poisoned())?;
+        if let Some(map) = maps.get(&path) {
+            return Ok(map.clone());
+        }
+        let map = Arc::new(read_index_db(&path)?);
+        println!("[+] Loaded {} ({} entries)", &path, map.len());
+        maps.insert(path, map.clone());
+        Ok(map)
+    }
+
+    //File name -> path in romfs
+    pub fn filename_to_localpath(&self, config: &TotkConfig) -> io::Result<Arc<HashMap<String, String>>> {
+        self.get(config, FILENAME_TO_LOCALPATH_DB)
+    }
+
+    //Internal path -> romfs path of the sarc holding it
+    pub fn internal_filepaths(&self, config: &TotkConfig) -> io::Result<Arc<HashMap<String, String>>> {
+        self.get(config, INTERNAL_FILEPATHS_DB)
+    }
+
+    //Internal path -> sha256 of the vanilla entry
+    pub fn sarc_hashes(&self, config: &TotkConfig) -> io::Result<Arc<HashMap<String, String>>> {
+        self.get(config, SARC_SHA256_DB)
+    }
+
+    pub fn localpath(&self, config: &TotkConfig, filename: &str) -> Option<String> {
+        self.filename_to_localpath(config).ok()?.get(filename).cloned()
+    }
+
+    pub fn sarc_of(&self, config: &TotkConfig, internal_path: &str) -> Option<String> {
+        self.internal_filepaths(config).ok()?.get(internal_path).cloned()
+    }
+
+    pub fn vanila_hash(&self, config: &TotkConfig, internal_path: &str) -> Option<String> {
+        self.sarc_hashes(config).ok()?.get(internal_path).cloned()
+    }
+
+    //Known RSTB entry paths, not version specific
+    pub fn rstb_paths(&self) -> io::Result<Arc<Vec<String>>> {
+        let mut rstb_paths = self.rstb_paths.lock().map_err(|_| poisoned())?;
+        if let Some(paths) = rstb_paths.as_ref() {
+            return Ok(paths.clone());
+        }
+        let json_zlibdata = fs::read(RSTB_PATHS_DB)?;
+        let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
+        let mut json_str = String::new();
+        decoder.read_to_string(&mut json_str)?;
+        let paths: Arc<Vec<String>> = Arc::new(serde_json::from_str(&json_str)?);
+        *rstb_paths = Some(paths.clone());
+        Ok(paths)
+    }
+
+    // Drops everything loaded, after the databases were regenerated
+    pub fn clear(&self) {
+        if let Ok(mut maps) = self.maps.lock() {
+            maps.clear();
+        }
+        if let Ok(mut rstb_paths) = self.rstb_paths.lock() {
+            *rstb_paths = None;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression as ZlibLevel;
+use serde::Serialize;
+
+use crate::file_format::Pack::PackFile;
+use crate::Batch::{collect_files, for_each_file_parallel, worker_count, BatchProgress, Failures};
+use crate::Settings::makedirs;
+use crate::Zstd::{sha256, TotkZstd};
+
+// Rebuilds the lookup databases from the user's own romfs dump, so vanilla comparisons match their game version.
+// Written to bin/index/<game version>/ (version from System/RegionLangMask.txt), same format as the prebuilt
+// ones in bin/: zlib compressed json maps
+//   totk_sarc_sha256.bin:           internal path -> sha256 of the entry
+//   totk_filename_to_localpath.bin: file name -> path relative to romfs
+//   totk_internal_filepaths.bin:    internal path -> path of the sarc holding it, relative to romfs
+// Lookups (TotkConfig::get_index_db_path) use the indexed one when it exists for the detected version.
+pub const INDEX_DIR: &str = "bin/index";
+pub const SARC_SHA256_DB: &str = "totk_sarc_sha256.bin";
+pub const FILENAME_TO_LOCALPATH_DB: &str = "totk_filename_to_localpath.bin";
+pub const INTERNAL_FILEPATHS_DB: &str = "totk_internal_filepaths.bin";
+pub const INDEX_META: &str = "index.json";
+
+const SARC_EXTENSIONS: [&str; 5] = [".pack", ".sarc", ".bfarc", ".blarc", ".genvb"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexReport {
+    pub game_version: String,
+    pub output: String,
+    pub files: usize,
+    pub sarcs: usize,
+    pub sarc_entries: usize,
+    pub failures: Failures,
+}
+
+impl IndexReport {
+    pub fn summary(&self) -> String {
+        let mut res = format!(
+            "Indexed {} files and {} sarc entries from {} archives for game version {}",
+            self.files, self.sarc_entries, self.sarcs, self.game_version
+        );
+        if !self.failures.is_empty() {
+            res.push_str(&format!(", {} archives failed", self.failures.len()));
+        }
+        res
+    }
+}
+
+// Entries of one archive: (internal path, sha256)
+struct SarcIndex {
+    rel_path: String,
+    entries: Vec<(String, String)>,
+}
+
+pub fn read_index_db<P: AsRef<Path>>(path: P) -> io::Result<HashMap<String, String>> {
+    let json_zlibdata = fs::read(path)?;
+    let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
+    let mut json_str = String::new();
+    decoder.read_to_string(&mut json_str)?;
+    let res: HashMap<String, String> = serde_json::from_str(&json_str)?;
+    Ok(res)
+}
+
+fn write_index_db(path: &Path, map: &BTreeMap<String, String>) -> io::Result<()> {
+    let json_str = serde_json::to_string(map)?;
+    let mut encoder = ZlibEncoder::new(Vec::new(), ZlibLevel::default());
+    encoder.write_all(json_str.as_bytes())?;
+    fs::write(path, encoder.finish()?)
+}
+
+pub fn is_sarc_path(rel_path: &str) -> bool {
+    let name = rel_path.to_ascii_lowercase();
+    let name = name.strip_suffix(".zs").unwrap_or(&name);
+    SARC_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+fn index_sarc(zstd: Arc<TotkZstd>, path: &Path, rel_path: &str) -> io::Result<SarcIndex> {
+    let pack = PackFile::new(path, zstd)?;
+    let mut entries = Vec::new();
+    for file in pack.sarc.files() {
+        if let Some(name) = file.name {
+            entries.push((name.to_string(), sha256(file.data().to_vec())));
+        }
+    }
+    Ok(SarcIndex {
+        rel_path: rel_path.to_string(),
+        entries,
+    })
+}
+
+pub fn index_dir(game_version: &str) -> PathBuf {
+    PathBuf::from(INDEX_DIR).join(game_version)
+}
+
+// Walks the whole romfs, archives are opened on all cores. Sorted by path, so with duplicates the first one wins
+// the same way on every run
+pub fn build_romfs_index<F>(zstd: Arc<TotkZstd>, on_progress: F) -> io::Result<IndexReport>
+where
+    F: Fn(&BatchProgress) + Sync,
+{
+    let config = zstd.totk_config.clone();
+    let romfs = PathBuf::from(&config.romfs);
+    if !zstd.is_valid() || !romfs.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No valid romfs path in config"));
+    }
+    if config.game_version.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to detect game version, System/RegionLangMask.txt missing from romfs",
+        ));
+    }
+    let files = collect_files(&romfs);
+
+    let mut filename_to_localpath: BTreeMap<String, String> = BTreeMap::new();
+    for (path, rel_path) in files.iter() {
+        if let Some(name) = path.file_name() {
+            filename_to_localpath
+                .entry(name.to_string_lossy().to_string())
+                .or_insert_with(|| rel_path.clone());
+        }
+    }
+    let sarcs: Vec<(PathBuf, String)> = files.iter().filter(|(_, rel_path)| is_sarc_path(rel_path)).cloned().collect();
+    println!("[+] Indexing {} archives from {:?} on {} threads", sarcs.len(), &romfs, worker_count(sarcs.len()));
+
+    let results: Mutex<Vec<SarcIndex>> = Mutex::new(Vec::new());
+    let failures: Mutex<Failures> = Mutex::new(Failures::default());
+    let process = |path: &Path, rel_path: &str| match index_sarc(zstd.clone(), path, rel_path) {
+        Ok(index) => results.lock().unwrap_or_else(|e| e.into_inner()).push(index),
+        Err(e) => failures.lock().unwrap_or_else(|e| e.into_inner()).push_error(rel_path, &e),
+    };
+    for_each_file_parallel(&sarcs, None, process, on_progress);
+
+    let mut results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    results.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    let mut sarc_sha256: BTreeMap<String, String> = BTreeMap::new();
+    let mut internal_filepaths: BTreeMap<String, String> = BTreeMap::new();
+    for sarc in results.iter() {
+        for (internal_path, hash) in sarc.entries.iter() {
+            sarc_sha256.entry(internal_path.clone()).or_insert_with(|| hash.clone());
+            internal_filepaths
+                .entry(internal_path.clone())
+                .or_insert_with(|| sarc.rel_path.clone());
+        }
+    }
+
+    let out_dir = index_dir(&config.game_version);
+    makedirs(&out_dir.join(INDEX_META))?;
+    write_index_db(&out_dir.join(SARC_SHA256_DB), &sarc_sha256)?;
+    write_index_db(&out_dir.join(FILENAME_TO_LOCALPATH_DB), &filename_to_localpath)?;
+    write_index_db(&out_dir.join(INTERNAL_FILEPATHS_DB), &internal_filepaths)?;
+
+    let mut failures = failures.into_inner().unwrap_or_else(|e| e.into_inner());
+    failures.sort_by(|a, b| a.path.cmp(&b.path));
+    let report = IndexReport {
+        game_version: config.game_version.clone(),
+        output: out_dir.to_string_lossy().replace("\\", "/"),
+        files: files.len(),
+        sarcs: results.len(),
+        sarc_entries: sarc_sha256.len(),
+        failures,
+    };
+    fs::write(out_dir.join(INDEX_META), serde_json::to_string_pretty(&report)?)?;
+    config.index.clear();
+    Ok(report)
+}
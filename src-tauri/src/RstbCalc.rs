@@ -0,0 +1,219 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::file_format::Rstb::{walk_mod_romfs, Restbl, RstbResource};
+use crate::Zstd::{sha256, TotkZstd, ZsDicType};
+
+// Resource size table for a mod: every file the mod adds or changes (loose or inside its packs) gets an
+// estimated size, entries are added or grown to it, never shrunk. Files identical to vanilla are skipped.
+// The estimates err on the large side, an entry too large only costs memory, one too small crashes the game.
+//   BYML (bgyml, bcett):   (size + 1000) * 8, parsed containers are much larger than the binary
+//   AINB:                  size + 392
+//   ASB:                   (size + 552) * 2
+//   BFEVFL, MSBT:          (size + 1500) * 2
+//   Pack, SARC:            size + 4096, entries stay in the archive buffer
+//   Anything else:         (size + 1500) * 4
+// Sizes are of the decompressed data rounded up to 32 bytes.
+// Where the numbers come from:
+//   Rounding to 32 bytes and a fixed overhead per type is the form of the BotW `rstb` library
+//   (leoetlino/rstb, SizeCalculator), its constants are for BotW and are not reused here.
+//   AINB, Pack, SARC: that form, with margins of our own. There is no published TotK table of overheads.
+//   BYML, ASB, BFEVFL, MSBT, anything else: no published formula either, the factors are our own and
+//   deliberately generous. estimate_size's tests pin each of them, so a change to one is a visible decision.
+// The source table is the mod's own one if it has it, else the one of the romfs dump, the result goes to the mod.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RstbChange {
+    pub name: String,
+    pub old: Option<u32>,
+    pub new: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RstbCalcReport {
+    pub dry_run: bool,
+    pub output: String,
+    pub added: Vec<RstbChange>,
+    pub grown: Vec<RstbChange>,
+    pub untouched: Vec<String>, //modified or added, current size already enough
+    pub vanila: usize,          //identical to vanilla, not looked at
+}
+
+impl RstbCalcReport {
+    pub fn summary(&self) -> String {
+        let changes = match self.dry_run {
+            true => format!("Would add {} and grow {}", self.added.len(), self.grown.len()),
+            false => format!("Added {} and grew {}", self.added.len(), self.grown.len()),
+        };
+        format!(
+            "{} RSTB entries, {} left untouched ({} vanilla files skipped)",
+            changes,
+            self.untouched.len(),
+            self.vanila
+        )
+    }
+
+    pub fn listing(&self, max_lines: usize) -> String {
+        let mut lines: Vec<String> = self
+            .added
+            .iter()
+            .map(|c| format!("+ {} = {}", c.name, c.new))
+            .chain(
+                self.grown
+                    .iter()
+                    .map(|c| format!("^ {}: {} -> {}", c.name, c.old.unwrap_or_default(), c.new)),
+            )
+            .collect();
+        let total = lines.len();
+        lines.truncate(max_lines);
+        if total > max_lines {
+            lines.push(format!("... and {} more", total - max_lines));
+        }
+        lines.join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.grown.is_empty()
+    }
+}
+
+pub fn rstb_local_path(game_version: &str) -> String {
+    format!("System/Resource/ResourceSizeTable.Product.{}.rsizetable.zs", game_version)
+}
+
+#[inline]
+fn round32(size: u64) -> u64 {
+    (size + 31) & !31
+}
+
+// `name` is the RSTB name (no .zs), `size` of the decompressed data
+pub fn estimate_size(name: &str, size: usize) -> u32 {
+    let name = name.to_ascii_lowercase();
+    let size = round32(size as u64);
+    let ext = name.rsplit('/').next().unwrap_or_default().rsplit('.').next().unwrap_or_default();
+    let res = match ext {
+        "bgyml" | "byml" => (size + 1000) * 8,
+        "ainb" => size + 392,
+        "asb" => (size + 552) * 2,
+        "bfevfl" | "msbt" => (size + 1500) * 2,
+        "pack" | "sarc" => size + 4096,
+        _ => (size + 1500) * 4,
+    };
+    res.min(u32::MAX as u64) as u32
+}
+
+// Loose files are compared with the same path in the dump, pack entries with the indexed vanilla hashes
+fn is_vanila(zstd: &TotkZstd, mod_romfs: &Path, resource: &RstbResource, name: &str, data: &[u8]) -> bool {
+    let config = &zstd.totk_config;
+    match resource {
+        RstbResource::File(path) => {
+            let rel_path = Path::new(path).strip_prefix(mod_romfs).unwrap_or(Path::new(path));
+            match fs::read(PathBuf::from(&config.romfs).join(rel_path)) {
+                Ok(vanila_data) => zstd.plain_data(vanila_data) == data,
+                Err(_) => false,
+            }
+        }
+        RstbResource::PackEntry(raw) => config.index.vanila_hash(config, name) == Some(sha256(raw.to_vec())),
+    }
+}
+
+pub fn regenerate_rstb(zstd: Arc<TotkZstd>, mod_romfs: &Path, dry_run: bool) -> io::Result<RstbCalcReport> {
+    if !mod_romfs.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Folder not found: {:?}", mod_romfs)));
+    }
+    let config = zstd.totk_config.clone();
+    if !zstd.is_valid() || config.game_version.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No valid romfs path in config, game version unknown"));
+    }
+    let rstb_path = rstb_local_path(&config.game_version);
+    let output = mod_romfs.join(&rstb_path);
+    let source = if output.exists() { output.clone() } else { PathBuf::from(&config.romfs).join(&rstb_path) };
+    let mut restbl = Restbl::from_path(&source, zstd.clone())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Unable to open RSTB: {:?}", &source)))?;
+    //vanilla tables have no dictionary, a mod table written with one by another tool is not kept that way
+    restbl.zs_dict = Some(ZsDicType::Empty);
+
+    let mut report = RstbCalcReport {
+        dry_run,
+        output: output.to_string_lossy().replace("\\", "/"),
+        ..Default::default()
+    };
+    let mod_romfs_path = mod_romfs.to_string_lossy().replace("\\", "/");
+    let mut estimates: Vec<(String, u32)> = Vec::new();
+    walk_mod_romfs(&mod_romfs_path, &[], zstd.clone(), |name, resource| {
+        if name.to_ascii_lowercase().starts_with("system/resource/resourcesizetable") {
+            return;
+        }
+        let data = match &resource {
+            RstbResource::File(path) => match fs::read(path) {
+                Ok(data) => zstd.plain_data(data),
+                Err(_) => return,
+            },
+            RstbResource::PackEntry(raw) => zstd.plain_data(raw.to_vec()),
+        };
+        if is_vanila(&zstd, Path::new(&mod_romfs_path), &resource, &name, &data) {
+            report.vanila += 1;
+            return;
+        }
+        let size = estimate_size(&name, data.len());
+        estimates.push((name, size));
+    });
+    //the same entry can come from several packs, the largest estimate wins
+    estimates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    estimates.dedup_by(|a, b| a.0 == b.0);
+
+    for (name, new) in estimates {
+        match restbl.table.get(name.clone()) {
+            None => {
+                restbl.table.set(name.clone(), new);
+                report.added.push(RstbChange { name, old: None, new });
+            }
+            Some(old) if old < new => {
+                restbl.table.set(name.clone(), new);
+                report.grown.push(RstbChange { name, old: Some(old), new });
+            }
+            Some(_) => report.untouched.push(name),
+        }
+    }
+    if !dry_run && !report.is_empty() {
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        restbl.save(&report.output)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_size_per_type() {
+        //100 bytes round up to 128
+        assert_eq!(estimate_size("Banc/Test.bcett.byml", 100), (128 + 1000) * 8);
+        assert_eq!(estimate_size("Component/Test.game__component__Test.bgyml", 100), (128 + 1000) * 8);
+        assert_eq!(estimate_size("AI/Test.ainb", 100), 128 + 392);
+        assert_eq!(estimate_size("AS/Test.asb", 100), (128 + 552) * 2);
+        assert_eq!(estimate_size("Event/EventFlow/Test.bfevfl", 100), (128 + 1500) * 2);
+        assert_eq!(estimate_size("Mals/USen.Product.100.sarc/Test.msbt", 100), (128 + 1500) * 2);
+        assert_eq!(estimate_size("Pack/Actor/Test.pack", 100), 128 + 4096);
+        assert_eq!(estimate_size("Mals/USen.Product.100.sarc", 100), 128 + 4096);
+    }
+
+    #[test]
+    fn estimate_size_fallback_and_edges() {
+        assert_eq!(estimate_size("Model/Test.bfres", 100), (128 + 1500) * 4);
+        assert_eq!(estimate_size("NoExtension", 100), (128 + 1500) * 4);
+        //the extension is matched case insensitively, only on the last path part
+        assert_eq!(estimate_size("AI/TEST.AINB", 100), 128 + 392);
+        assert_eq!(estimate_size("Test.pack/Model", 100), (128 + 1500) * 4);
+        assert_eq!(estimate_size("AI/Test.ainb", 0), 392);
+        assert_eq!(estimate_size("AI/Test.ainb", 32), 32 + 392);
+        assert_eq!(estimate_size("Model/Test.bfres", u32::MAX as usize), u32::MAX);
+    }
+}
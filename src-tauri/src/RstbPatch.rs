@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use restbl::ResourceSizeTable;
+use serde::{Deserialize, Serialize};
+
+use crate::file_format::Rstb::{parse_rstb_hash, Restbl, RstbEntries};
+use crate::RstbCalc::rstb_local_path;
+
+// RSTB edits carried over game updates: the opened table is diffed against the vanilla one of its own version
+// (from the romfs dump, version from the file name) and only the entries that differ are kept, with the vanilla
+// size next to the modded one:
+//   game_version: '121'
+//   entries:
+//     Pack/Actor/Foo.pack: {vanila: 4096, size: 8192}   modified
+//     Pack/Actor/Bar.pack: {size: 8192}                 added
+//     Pack/Actor/Baz.pack: {vanila: 4096}               removed
+// Applied to a table of any version, an entry whose current size is not the patch's vanilla one is a conflict
+// (the update changed it): the larger of the two sizes is kept, removals are not done.
+// YAML or JSON by extension. Entries no known path hashes to are in the patch by hash, as '0x1234abcd' keys.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RstbPatchEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vanila: Option<u32>, //None: added by the mod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>, //None: removed by the mod
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RstbPatch {
+    pub game_version: String,
+    pub entries: BTreeMap<String, RstbPatchEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RstbConflict {
+    pub name: String,
+    pub vanila: Option<u32>,  //in the patch
+    pub current: Option<u32>, //in the target table
+    pub size: Option<u32>,    //in the patch
+    pub kept: Option<u32>,    //what the target ends with
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RstbApplyReport {
+    pub patch_version: String,
+    pub target_version: String,
+    pub applied: usize,
+    pub unchanged: usize, //already at the patched size
+    pub conflicts: Vec<RstbConflict>,
+}
+
+impl RstbApplyReport {
+    pub fn summary(&self) -> String {
+        let mut res = format!(
+            "Applied {} RSTB entries from version {} to version {}, {} already patched",
+            self.applied, self.patch_version, self.target_version, self.unchanged
+        );
+        if !self.conflicts.is_empty() {
+            res.push_str(&format!(", {} conflicts (vanilla size changed)", self.conflicts.len()));
+        }
+        res
+    }
+
+    pub fn conflicts_report(&self, max_lines: usize) -> String {
+        let size_str = |size: Option<u32>| size.map(|s| s.to_string()).unwrap_or("none".to_string());
+        let mut lines: Vec<String> = self
+            .conflicts
+            .iter()
+            .take(max_lines)
+            .map(|c| {
+                format!(
+                    "{}: vanilla {} -> {}, patch {}, kept {}",
+                    c.name,
+                    size_str(c.vanila),
+                    size_str(c.current),
+                    size_str(c.size),
+                    size_str(c.kept)
+                )
+            })
+            .collect();
+        if self.conflicts.len() > max_lines {
+            lines.push(format!("... and {} more", self.conflicts.len() - max_lines));
+        }
+        lines.join("\n")
+    }
+}
+
+// ResourceSizeTable.Product.121.rsizetable.zs -> 121
+pub fn rstb_game_version(name: &str) -> Option<String> {
+    let lower = name.to_ascii_lowercase();
+    let ver = lower.strip_prefix("resourcesizetable.product.")?.split('.').next()?;
+    if ver.is_empty() || !ver.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(ver.to_string())
+}
+
+fn is_yaml_path(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    name.ends_with(".yml") || name.ends_with(".yaml")
+}
+
+// Every entry of the table by patch key: the path when known, else the hash
+fn patch_entries(table: &ResourceSizeTable, known_names: &[String]) -> io::Result<BTreeMap<String, u32>> {
+    let (mut res, unresolved) = RstbEntries::from_table(table)?.resolve(known_names);
+    res.extend(unresolved.iter().map(|(hash, size)| (format!("0x{:08x}", hash), *size)));
+    Ok(res)
+}
+
+// Named keys go to the table right away, hash keys are collected and written in one pass at the end
+fn set_patch_entry(table: &mut ResourceSizeTable, hash_edits: &mut Vec<(u32, Option<u32>)>, name: &str, size: Option<u32>) {
+    match (parse_rstb_hash(name), size) {
+        (Some(hash), size) => hash_edits.push((hash, size)),
+        (None, Some(size)) => {
+            table.set(name.to_string(), size);
+        }
+        (None, None) => {
+            table.remove(name.to_string());
+        }
+    }
+}
+
+impl RstbPatch {
+    pub fn from_tables(modded: &Restbl, vanila: &Restbl, game_version: &str) -> io::Result<RstbPatch> {
+        //the same known paths for both, so a hash gets the same key in each
+        let known: Vec<String> = modded.hash_table.iter().chain(vanila.hash_table.iter()).cloned().collect();
+        let modded = patch_entries(&modded.table, &known)?;
+        let vanila = patch_entries(&vanila.table, &known)?;
+        let names: BTreeSet<&String> = modded.keys().chain(vanila.keys()).collect();
+        let mut entries = BTreeMap::new();
+        for name in names {
+            let size = modded.get(name).copied();
+            let vanila = vanila.get(name).copied();
+            if size != vanila {
+                entries.insert(name.clone(), RstbPatchEntry { vanila, size });
+            }
+        }
+        Ok(RstbPatch {
+            game_version: game_version.to_string(),
+            entries,
+        })
+    }
+
+    // The opened table against the vanilla table of the same version from the romfs dump
+    pub fn from_restbl(restbl: &Restbl) -> io::Result<RstbPatch> {
+        let game_version = rstb_game_version(&restbl.path.name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unable to get game version from file name: {}", &restbl.path.name),
+            )
+        })?;
+        let config = &restbl.zstd.totk_config;
+        let vanila_path = PathBuf::from(&config.romfs).join(rstb_local_path(&game_version));
+        if !vanila_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Vanilla RSTB for game version {} not in romfs (dump is version {})",
+                    &game_version, &config.game_version
+                ),
+            ));
+        }
+        let vanila = Restbl::from_path(&vanila_path, restbl.zstd.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Unable to open RSTB: {:?}", &vanila_path)))?;
+        RstbPatch::from_tables(restbl, &vanila, &game_version)
+    }
+
+    pub fn to_text(&self, yaml: bool) -> io::Result<String> {
+        if yaml {
+            return serde_yaml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = self.to_text(is_yaml_path(path.as_ref()))?;
+        fs::write(path, text)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<RstbPatch> {
+        let text = fs::read_to_string(path.as_ref())?;
+        if is_yaml_path(path.as_ref()) {
+            return serde_yaml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn apply(&self, table: &mut ResourceSizeTable, target_version: &str) -> io::Result<RstbApplyReport> {
+        let mut report = RstbApplyReport {
+            patch_version: self.game_version.clone(),
+            target_version: target_version.to_string(),
+            ..Default::default()
+        };
+        let hashes = RstbEntries::from_table(table)?.hashes;
+        let mut hash_edits: Vec<(u32, Option<u32>)> = Vec::new();
+        for (name, entry) in self.entries.iter() {
+            let current = match parse_rstb_hash(name) {
+                Some(hash) => hashes.get(&hash).copied(),
+                None => table.get(name.clone()),
+            };
+            if current == entry.size {
+                report.unchanged += 1;
+                continue;
+            }
+            if current == entry.vanila {
+                set_patch_entry(table, &mut hash_edits, name, entry.size);
+                report.applied += 1;
+                continue;
+            }
+            //vanilla size changed in the target, never go below either of them
+            let kept = match (entry.size, current) {
+                (Some(size), Some(cur)) => Some(size.max(cur)),
+                (Some(size), None) => Some(size),
+                (None, cur) => cur,
+            };
+            if kept != current && kept.is_some() {
+                set_patch_entry(table, &mut hash_edits, name, kept);
+            }
+            report.conflicts.push(RstbConflict {
+                name: name.clone(),
+                vanila: entry.vanila,
+                current,
+                size: entry.size,
+                kept,
+            });
+        }
+        if !hash_edits.is_empty() {
+            let mut entries = RstbEntries::from_table(table)?;
+            for (hash, size) in hash_edits {
+                match size {
+                    Some(size) => entries.hashes.insert(hash, size),
+                    None => entries.hashes.remove(&hash),
+                };
+            }
+            *table = entries.to_table()?;
+        }
+        Ok(report)
+    }
+}
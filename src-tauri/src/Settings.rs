@@ -9,21 +9,62 @@ use std::path::PathBuf;
 use std::process;
 use std::process::Command;
 use regex::Regex;
-use rfd::MessageDialog;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use updater::TotkbitsVersion::TotkbitsVersion;
 
+use crate::Interaction::UserInteraction;
 use crate::TotkConfig::TotkConfig;
 
+#[cfg(windows)]
 pub const BACKUP_UPDATER_NAME: &str = "backup_updater.exe";
+#[cfg(not(windows))]
+pub const BACKUP_UPDATER_NAME: &str = "backup_updater";
 
 
 pub const NO_WINDOW_FLAG: u32 = 0x08000000;
 
-#[tauri::command]
-pub fn get_startup_data(state: tauri::State<serde_json::Value>) -> Result<serde_json::Value, String> {
-    Ok((*state.inner()).clone())
+//external tools
+#[cfg(windows)]
+pub const PYTHON_EXE: &str = "bin/winpython/python-3.11.8.amd64/python.exe";
+#[cfg(not(windows))]
+pub const PYTHON_EXE: &str = "python3";
+#[cfg(windows)]
+pub const DOTNET_WRAPPER_EXE: &str = "bin/cs/DotNetWrapper.exe";
+#[cfg(not(windows))]
+pub const DOTNET_WRAPPER_EXE: &str = "bin/cs/DotNetWrapper";
+pub const DOTNET_WRAPPER_DLL: &str = "bin/cs/DotNetWrapper.dll";
+pub const PYTHON_SCRIPT: &str = "totkbits.py";
+
+// Looks up an executable in PATH, like `which`
+pub fn find_in_path(name: &str) -> Option<String> {
+    let paths = env::var_os("PATH")?;
+    for dir in env::split_paths(&paths) {
+        let candidate = dir.join(format!("{}{}", name, env::consts::EXE_SUFFIX));
+        if candidate.is_file() {
+            return Some(candidate.to_string_lossy().to_string().replace("\\", "/"));
+        }
+    }
+    None
+}
+
+// creation_flags only exists on windows (os::windows::process::CommandExt),
+// other platforms have no console window to hide so the flags are ignored there
+pub trait NoWindow {
+    fn no_window(&mut self, flags: u32) -> &mut Command;
+}
+
+impl NoWindow for Command {
+    #[cfg(windows)]
+    fn no_window(&mut self, flags: u32) -> &mut Command {
+        use std::os::windows::process::CommandExt;
+        self.creation_flags(flags)
+    }
+
+    #[cfg(not(windows))]
+    fn no_window(&mut self, _flags: u32) -> &mut Command {
+        self
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -34,12 +75,14 @@ pub struct StartupData {
 }
 
 impl StartupData {
-    pub fn new() -> io::Result<Self> {
+    pub fn new(ui: &dyn UserInteraction) -> io::Result<Self> {
         let args: Vec<String> = env::args().collect();
         let argv1 = args.get(1).cloned().unwrap_or_default();
-        let config = TotkConfig::safe_new().unwrap_or(TotkConfig::default());
+        let config = TotkConfig::safe_new(ui).unwrap_or(TotkConfig::default());
         let zstd_msg = if config.is_valid() {
             ""
+        } else if config.has_zsdic() {
+            "No romfs, vanilla comparison disabled"
         } else {
             "ZSTD disabled"
         };
@@ -271,30 +314,29 @@ pub fn process_inline_content(mut input: String, inline_count: usize) -> String
 
 
 
-pub fn spawn_updater(latest_ver: &str) -> io::Result<()> {
+pub fn spawn_updater(latest_ver: &str, ui: &dyn UserInteraction) -> io::Result<()> {
     let version = env!("CARGO_PKG_VERSION").to_string();
-    if MessageDialog::new()
-        .set_title("Update Available")
-        .set_description(&format!("Update available: {} -> {}\nTotkBits will be closed, make sure to save all opened files.\nProceed?", version, latest_ver))
-        .set_buttons(rfd::MessageButtons::YesNo)
-        .show()
-        != rfd::MessageDialogResult::Yes
-    {
+    if !ui.confirm(
+        "Update Available",
+        &format!("Update available: {} -> {}\nTotkBits will be closed, make sure to save all opened files.\nProceed?", version, latest_ver),
+    ) {
         return Ok(());
     }
 
     let mut upd_exe = if cfg!(debug_assertions) {
-        "../ext_projects/updater/target/debug/updater.exe"
+        format!("../ext_projects/updater/target/debug/updater{}", env::consts::EXE_SUFFIX)
     } else {
-        "updater.exe"
-    }.to_string();
+        format!("updater{}", env::consts::EXE_SUFFIX)
+    };
     let upd_path = fs::canonicalize(&upd_exe)?;
     if !upd_path.exists() {
         println!("[-] Updater executable not found: {}", &upd_exe);
         process::exit(1);
     }
     upd_exe = upd_path.to_string_lossy().to_string().replace("\\\\?\\", "");
-    let backup_upd_exe = format!("{}\\{}", Pathlib::new(&upd_exe).parent, BACKUP_UPDATER_NAME).replace("/", "\\");
+    let backup_upd_exe = format!("{}/{}", Pathlib::new(&upd_exe).parent, BACKUP_UPDATER_NAME);
+    #[cfg(windows)]
+    let backup_upd_exe = backup_upd_exe.replace("/", "\\");
     if Path::new(&backup_upd_exe).exists() {
         println!("[+] Removing old backup updater: {}", &backup_upd_exe);
         fs::remove_file(&backup_upd_exe)?;
@@ -302,10 +344,15 @@ pub fn spawn_updater(latest_ver: &str) -> io::Result<()> {
     println!("[+] Backing up: {}", &backup_upd_exe);
     fs::copy(&upd_exe, &backup_upd_exe)?;
     println!("[+] Updater executable found: {}", &backup_upd_exe);
-    let p = Command::new("cmd")
-        .arg("/c")
-        .arg("start")
-        .arg(&backup_upd_exe)
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut c = Command::new("cmd");
+        c.arg("/c").arg("start").arg(&backup_upd_exe);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut cmd = Command::new(&backup_upd_exe);
+    let p = cmd
         .arg(&version)
         .arg(latest_ver)
         // .arg(process::id().to_string())
@@ -1,20 +1,25 @@
 //tauri commands
-use crate::{
-    Open_and_Save::SendData, Settings::{spawn_updater, NO_WINDOW_FLAG}, TotkApp::{SaveData, TotkBitsApp}, TotkConfig::TotkConfig, Zstd::{TotkZstd, COMPRESSION_LEVEL}
+use totkbits_core::{
+    Batch::{batch_zstd_folder as batch_zstd_folder_impl, is_progress_step, BatchMode, BatchProgress}, file_format::Wrapper::{check_external_tools as check_external_tools_impl, tools_report}, FolderSearch::{search_folder, SearchMatch, SearchOptions}, Interaction::UserInteraction, Open_and_Save::SendData, RomfsIndexer::build_romfs_index as build_romfs_index_impl, Settings::{spawn_updater, NoWindow, NO_WINDOW_FLAG}, TotkApp::{SaveData, TotkBitsApp}, TotkConfig::TotkConfig, Zstd::TotkZstd
 };
 use rfd::MessageDialog;
+use crate::Dialogs::RfdDialogs;
 use serde::Deserialize;
 use updater::TotkbitsVersion::TotkbitsVersion;
 use std::{
-    collections::HashMap, env, error::Error, os::windows::process::CommandExt, path::Path, process::{self, Command}, sync::{Arc, Mutex}
+    collections::HashMap, env, error::Error, path::{Path, PathBuf}, process::{self, Command}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, thread
 };
 use tauri::Manager;
 use reqwest::blocking::{get, Client};
 
+#[tauri::command]
+pub fn get_startup_data(state: tauri::State<serde_json::Value>) -> Result<serde_json::Value, String> {
+    Ok((*state.inner()).clone())
+}
+
 #[tauri::command]
 pub fn restart_app() -> Option<()> {
     let totkbits_exe = env::current_exe().ok()?;
-    let no_window_flag = NO_WINDOW_FLAG;
     if let rfd::MessageDialogResult::No = MessageDialog::new()
         .set_title("Warning")
         .set_description("Totkbits will be restarted, all unsaved progress will be lost. Proceed?")
@@ -24,8 +29,9 @@ pub fn restart_app() -> Option<()> {
         return Some(());
     }
     // let _ = Command::new(totkbits_exe)
+    #[cfg(windows)]
     let p = Command::new("cmd")
-        .creation_flags(no_window_flag)
+        .no_window(NO_WINDOW_FLAG)
         .args([
             "/C",
             "start",
@@ -33,6 +39,8 @@ pub fn restart_app() -> Option<()> {
             &totkbits_exe.to_string_lossy().into_owned(),
         ])
         .spawn();
+    #[cfg(not(windows))]
+    let p = Command::new(&totkbits_exe).spawn();
         // .map(|_| ())?;
         // .ok()?;
     match p {
@@ -49,7 +57,7 @@ pub fn edit_config(app_handle: tauri::AppHandle) -> Option<()> {
     let no_window_flag = NO_WINDOW_FLAG;
     let binding = app_handle.state::<Mutex<TotkBitsApp>>();
     let app = binding.lock().expect("Failed to lock state");
-    if !app.zstd.clone().totk_config.is_valid() {
+    if !app.zstd.clone().totk_config.has_zsdic() {
         return None;
     }
     let file_path = app.zstd.clone().totk_config.config_path.clone();
@@ -57,15 +65,15 @@ pub fn edit_config(app_handle: tauri::AppHandle) -> Option<()> {
 
     let result = match os_type {
         "windows" => Command::new("cmd")
-            .creation_flags(no_window_flag)
+            .no_window(no_window_flag)
             .args(["/C", "start", "", &file_path])
             .status(),
         "macos" => Command::new("open")
-            .creation_flags(no_window_flag)
+            .no_window(no_window_flag)
             .arg(file_path)
             .status(),
         "linux" => Command::new("xdg-open")
-            .creation_flags(no_window_flag)
+            .no_window(no_window_flag)
             .arg(file_path)
             .status(),
         _ => Err(std::io::Error::new(
@@ -121,6 +129,83 @@ pub fn edit_internal_file(app_handle: tauri::AppHandle, path: String) -> Option<
     }
 }
 
+#[tauri::command]
+pub fn restore_vanila_sarc_entry(app_handle: tauri::AppHandle, internalPath: String) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.restore_vanila_entry(internalPath)
+}
+
+#[tauri::command]
+pub fn minimize_sarc(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.minimize_sarc()
+}
+
+#[tauri::command]
+pub fn minimize_mod_folder(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let app = binding.lock().expect("Failed to lock state");
+    app.minimize_mod_folder()
+}
+
+#[tauri::command]
+pub fn regenerate_mod_rstb(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let app = binding.lock().expect("Failed to lock state");
+    app.regenerate_mod_rstb()
+}
+
+#[tauri::command]
+pub fn rstb_to_text(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.rstb_to_text()
+}
+
+#[tauri::command]
+pub fn export_rstb_patch(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let app = binding.lock().expect("Failed to lock state");
+    app.export_rstb_patch()
+}
+
+#[tauri::command]
+pub fn apply_rstb_patch(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.apply_rstb_patch()
+}
+
+#[tauri::command]
+pub fn export_pack_diff_report(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let app = binding.lock().expect("Failed to lock state");
+    app.export_pack_diff_report()
+}
+
+#[tauri::command]
+pub fn undo_sarc_op(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.undo_sarc_op(false)
+}
+
+#[tauri::command]
+pub fn redo_sarc_op(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.undo_sarc_op(true)
+}
+
+#[tauri::command]
+pub fn close_nested_sarc(app_handle: tauri::AppHandle, level: usize) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let mut app = binding.lock().expect("Failed to lock state");
+    app.close_nested_sarc(level)
+}
+
 #[tauri::command]
 pub fn extract_folder_from_opened_sarc(app_handle: tauri::AppHandle, sourceFolder: String) -> Option<SendData> {
     let binding = app_handle.state::<Mutex<TotkBitsApp>>();
@@ -443,12 +528,22 @@ pub fn update_toml_config(app_handle: tauri::AppHandle, newConfig: HashMap<Strin
     if let Ok(_) = new_config_var.save() {
         is_saved_str = " saved";
     }
-    let available_str = "ZSTD available, options ".to_string() + is_saved_str;
-    let unavailable_str = "ZSTD unavailable, options ".to_string() + is_saved_str;
-    match TotkZstd::new(Arc::new(new_config_var), COMPRESSION_LEVEL) {
+    let mut available_str = "ZSTD available, options ".to_string() + is_saved_str;
+    let mut unavailable_str = "ZSTD unavailable, options ".to_string() + is_saved_str;
+    if let Err(e) = new_config_var.compression_policy() {
+        available_str.push_str(&format!(", {} (defaults used)", e));
+        unavailable_str.push_str(&format!(", {} (defaults used)", e));
+    }
+    match TotkZstd::new(Arc::new(new_config_var), app.zstd.ui.clone()) {
         Ok(new_zstd) => {
             app.zstd = Arc::new(new_zstd);
-            let st = if app.zstd.clone().totk_config.is_valid() {available_str} else {unavailable_str};
+            let st = if !app.zstd.has_zstd() {
+                unavailable_str
+            } else if !app.zstd.is_valid() {
+                available_str + ", no romfs: vanilla comparison unavailable"
+            } else {
+                available_str
+            };
             send_data.status_text = st;
         } // Safely return the result if present
         Err(_) => {
@@ -458,6 +553,169 @@ pub fn update_toml_config(app_handle: tauri::AppHandle, newConfig: HashMap<Strin
     return Some(send_data);
 }
 
+#[tauri::command]
+pub fn check_external_tools(newConfig: HashMap<String, serde_json::Value>) -> Option<SendData> {
+    //checks the paths currently in the editor, saved or not
+    let mut config = TotkConfig::default();
+    config.update_from_json_data(newConfig);
+    let checks = check_external_tools_impl(&config);
+    let report = tools_report(&checks);
+    println!("{}", &report);
+    let mut send_data = SendData::default();
+    let failed = checks.iter().filter(|c| c.result.is_err()).count();
+    if failed == 0 {
+        send_data.status_text = "All external converters available".to_string();
+    } else {
+        send_data.status_text = format!("{} of {} external converters unavailable", failed, checks.len());
+    }
+    RfdDialogs.message("External converters", &report);
+    Some(send_data)
+}
+
+
+#[tauri::command]
+pub fn batch_zstd_folder(app_handle: tauri::AppHandle, window: tauri::Window, mode: String) -> Option<SendData> {
+    let mode = BatchMode::from_str(&mode)?;
+    let title = match mode {
+        BatchMode::Compress => "Choose folder to compress",
+        BatchMode::Decompress => "Choose folder to decompress",
+    };
+    let src = RfdDialogs.pick_folder(title)?;
+    //same folder as source keeps the originals next to the results
+    let dest = RfdDialogs.pick_folder("Choose output folder")?;
+    let zstd = {
+        let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+        let app = binding.lock().expect("Failed to lock state");
+        app.zstd.clone()
+    };
+    let mut started = SendData::default();
+    started.status_text = format!("Processing {} ...", src.to_string_lossy().replace("\\", "/"));
+    //runs in the background, the app stays usable. Progress and the result come as events
+    thread::spawn(move || {
+        let mut send_data = SendData::default();
+        let on_progress = |progress: &BatchProgress| {
+            if is_progress_step(progress) {
+                let _ = window.emit("batch-progress", progress);
+            }
+        };
+        match batch_zstd_folder_impl(&zstd, &src, &dest, mode, on_progress) {
+            Ok(report) => {
+                send_data.status_text = report.summary(mode);
+                if !report.failures.is_empty() {
+                    tauri::api::dialog::message(Some(&window), "Failed files", report.failures.report(30));
+                }
+            }
+            Err(e) => {
+                send_data.status_text = format!("Error: {}", e);
+                send_data.tab = "ERROR".to_string();
+            }
+        }
+        let _ = window.emit("batch-finished", send_data);
+    });
+    Some(started)
+}
+
+#[tauri::command]
+pub fn build_romfs_index(app_handle: tauri::AppHandle, window: tauri::Window) -> Option<SendData> {
+    let zstd = {
+        let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+        let app = binding.lock().expect("Failed to lock state");
+        app.zstd.clone()
+    };
+    let mut started = SendData::default();
+    started.status_text = format!("Indexing romfs {} ...", &zstd.totk_config.romfs);
+    //same events as batch_zstd_folder, the whole romfs takes a while
+    thread::spawn(move || {
+        let mut send_data = SendData::default();
+        let on_progress = |progress: &BatchProgress| {
+            if is_progress_step(progress) {
+                let _ = window.emit("batch-progress", progress);
+            }
+        };
+        match build_romfs_index_impl(zstd, on_progress) {
+            Ok(report) => {
+                send_data.status_text = report.summary();
+                if !report.failures.is_empty() {
+                    tauri::api::dialog::message(Some(&window), "Failed archives", report.failures.report(30));
+                }
+            }
+            Err(e) => {
+                send_data.status_text = format!("Error: {}", e);
+                send_data.tab = "ERROR".to_string();
+            }
+        }
+        let _ = window.emit("batch-finished", send_data);
+    });
+    Some(started)
+}
+
+#[tauri::command]
+pub fn search_in_folder(app_handle: tauri::AppHandle, window: tauri::Window, query: String, fileTypes: String, inRomfs: bool) -> Option<SendData> {
+    let zstd = {
+        let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+        let app = binding.lock().expect("Failed to lock state");
+        app.zstd.clone()
+    };
+    let mut started = SendData::default();
+    let options = match SearchOptions::new(&query, &fileTypes) {
+        Ok(options) => options,
+        Err(e) => {
+            started.status_text = format!("Error: {}", e);
+            started.tab = "ERROR".to_string();
+            return Some(started);
+        }
+    };
+    let folder = if inRomfs {
+        if !zstd.is_valid() {
+            started.status_text = "Error: No valid romfs path in config".to_string();
+            started.tab = "ERROR".to_string();
+            return Some(started);
+        }
+        PathBuf::from(&zstd.totk_config.romfs)
+    } else {
+        RfdDialogs.pick_folder("Choose mod folder to search")?
+    };
+    //every search gets its own flag, a search still winding down keeps its cancelled one
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+        let mut app = binding.lock().expect("Failed to lock state");
+        app.search_cancel.store(true, Ordering::SeqCst);
+        app.search_cancel = cancel.clone();
+    }
+    started.status_text = format!("Searching \"{}\" in {} ...", &query, folder.to_string_lossy().replace("\\", "/"));
+    //matches are streamed while the search runs, cancel_folder_search stops it
+    thread::spawn(move || {
+        let mut send_data = SendData::default();
+        let on_progress = |progress: &BatchProgress| {
+            if is_progress_step(progress) {
+                let _ = window.emit("search-progress", progress);
+            }
+        };
+        let on_matches = |matches: &[SearchMatch]| {
+            let _ = window.emit("search-matches", matches);
+        };
+        match search_folder(zstd, &folder, &options, &cancel, on_progress, on_matches) {
+            Ok(report) => send_data.status_text = report.summary(),
+            Err(e) => {
+                send_data.status_text = format!("Error: {}", e);
+                send_data.tab = "ERROR".to_string();
+            }
+        }
+        let _ = window.emit("search-finished", send_data);
+    });
+    Some(started)
+}
+
+#[tauri::command]
+pub fn cancel_folder_search(app_handle: tauri::AppHandle) -> Option<SendData> {
+    let binding = app_handle.state::<Mutex<TotkBitsApp>>();
+    let app = binding.lock().expect("Failed to lock state");
+    app.search_cancel.store(true, Ordering::SeqCst);
+    let mut send_data = SendData::default();
+    send_data.status_text = "Cancelling search...".to_string();
+    Some(send_data)
+}
 
 #[tauri::command]
 pub fn check_if_update_needed() -> String {
@@ -496,7 +754,7 @@ pub fn check_if_update_needed() -> String {
 #[tauri::command]
 pub fn update_app(latestVer: String) -> String {
     
-    if let Err(e) = spawn_updater(latestVer.as_str()) {
+    if let Err(e) = spawn_updater(latestVer.as_str(), &RfdDialogs) {
         return format!("Error spawning updater: {:?}", e);
     }
     // process::exit(1);
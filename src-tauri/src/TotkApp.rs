@@ -1,21 +1,28 @@
 use crate::file_format::BinTextFile::{BymlFile, OpenedFile};
 use crate::file_format::Esetb::Esetb;
-use crate::file_format::Pack::{PackComparer, SarcPaths};
+use crate::file_format::Rstb::{parse_rstb_hash, rstb_crc32};
+use crate::file_format::Pack::{PackComparer, SarcOp, SarcPaths};
 use crate::Comparer::DiffComparer;
+use crate::DiffReport::PackDiffReport;
+use crate::Interaction::UserInteraction;
+use crate::Minimize::{format_size, minimize_folder};
 use crate::Open_and_Save::{
     check_if_save_in_romfs, file_from_disk_to_senddata, get_binary_by_filetype, get_string_from_data, open_sarc, SaveFileDialog, SendData
 };
+use crate::RstbCalc::regenerate_rstb;
+use crate::RstbPatch::{rstb_game_version, RstbPatch};
 use crate::Settings::{ list_files_recursively, write_string_to_file, Pathlib};
 use crate::TotkConfig::TotkConfig;
-use crate::Zstd::{is_sarc_root_path, TotkFileType, TotkZstd, COMPRESSION_LEVEL};
-use rfd::{FileDialog, MessageDialog};
+use crate::Zstd::{is_sarc, is_sarc_root_path, is_zstd, TotkFileType, TotkZstd, ZsDicType};
 use roead::byml::Byml;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashSet;
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -29,19 +36,17 @@ pub struct TotkBitsApp<'a> {
     pub text: String,
     pub status_text: String,
     pub zstd: Arc<TotkZstd<'a>>,
-    // pub zstd_cpp: Arc<ZstdCppCompressor>,
     pub pack: Option<PackComparer<'a>>,
     pub internal_file: Option<InternalFile<'a>>,
+    pub search_cancel: Arc<AtomicBool>, //set to stop the running folder search
 }
 
-unsafe impl<'a> Send for TotkBitsApp<'a> {}
-
-impl Default for TotkBitsApp<'_> {
-    fn default() -> Self {
+impl TotkBitsApp<'_> {
+    pub fn new(ui: Arc<dyn UserInteraction>) -> Self {
         // match TotkConfig::from_toml() {
             // Ok(conf) => {
-                let totk_config: Arc<TotkConfig> = Arc::new(TotkConfig::from_toml().unwrap_or(TotkConfig::default()));
-                match TotkZstd::new(totk_config, COMPRESSION_LEVEL) {
+                let totk_config: Arc<TotkConfig> = Arc::new(TotkConfig::from_toml(ui.as_ref()).unwrap_or(TotkConfig::default()));
+                match TotkZstd::new(totk_config, ui) {
                     Ok(zstd) => {
                         let zstd: Arc<TotkZstd> = Arc::new(zstd);
                         return Self {
@@ -51,6 +56,7 @@ impl Default for TotkBitsApp<'_> {
                             zstd: zstd.clone(),
                             pack: None,
                             internal_file: None,
+                            search_cancel: Arc::new(AtomicBool::new(false)),
                         };
                     }
                     Err(_) => {
@@ -78,25 +84,65 @@ impl<'a> TotkBitsApp<'a> {
             data.tab = "RSTB".to_string();
 
             let entry_low = entry.to_lowercase();
+            //queries starting with 0x match the hashes too, paths and hash only entries alike
+            let hex_query = entry_low.strip_prefix("0x").unwrap_or_default();
+            let is_hex_query = !hex_query.is_empty() && hex_query.len() <= 8 && hex_query.chars().all(|c| c.is_ascii_hexdigit());
+            let mut listed: HashSet<&String> = HashSet::new();
             for elem in rstb.hash_table.iter() {
-                if elem.to_lowercase().contains(&entry_low) {
+                let hash = rstb_crc32(elem);
+                let is_match = elem.to_lowercase().contains(&entry_low)
+                    || (is_hex_query && format!("{:08x}", hash).contains(hex_query));
+                if is_match && listed.insert(elem) {
                     if let Some(val) = rstb.table.get(elem.clone()) {
                         if elem == &entry {
                             isDefaultAdded = true;
                         }
-                        data.rstb_paths
-                            .push(json!({ "path": elem.clone(), "val": val.to_string() }));
+                        data.rstb_paths.push(json!({
+                            "path": elem.clone(),
+                            "val": val.to_string(),
+                            "hash": format!("0x{:08x}", hash),
+                            "collisions": rstb.collisions(elem),
+                        }));
                     }
                 }
             }
-            if !isDefaultAdded {
+            if !isDefaultAdded && parse_rstb_hash(&entry).is_none() {
                 if let Some(val) = rstb.table.get(entry.clone()) {
                     //entry exists
-                    data.rstb_paths
-                        .push(json!({ "path": entry.clone(), "val": val.to_string() }));
+                    data.rstb_paths.push(json!({
+                        "path": entry.clone(),
+                        "val": val.to_string(),
+                        "hash": format!("0x{:08x}", rstb_crc32(&entry)),
+                        "collisions": rstb.collisions(&entry),
+                    }));
+                }
+            }
+            if is_hex_query {
+                match rstb.hash_only_entries() {
+                    Ok(hashes) => {
+                        for (hash, val) in hashes.iter() {
+                            let hash_str = format!("0x{:08x}", hash);
+                            if hash_str.contains(hex_query) {
+                                data.rstb_paths.push(json!({
+                                    "path": hash_str.clone(),
+                                    "val": val.to_string(),
+                                    "hash": hash_str,
+                                    "collisions": Vec::<String>::new(),
+                                }));
+                            }
+                        }
+                    }
+                    Err(err) => println!("[-] Unable to list RSTB hash entries: {:?}", err),
                 }
             }
             data.status_text = format!("Found entries: {}", data.rstb_paths.len());
+            if parse_rstb_hash(&entry).is_none() {
+                data.status_text.push_str(&format!(", CRC32 of {}: 0x{:08x}", &entry, rstb_crc32(&entry)));
+                let collisions = rstb.collisions(&entry);
+                if !collisions.is_empty() {
+                    data.status_text.push_str(&format!(" (collides with {})", collisions.join(", ")));
+                }
+            }
         } else {
             data.status_text = "Error: No RSTB opened".to_string();
             data.tab = "ERROR".to_string();
@@ -105,18 +151,41 @@ impl<'a> TotkBitsApp<'a> {
         Some(data)
     }
 
+    // Entries are paths, or raw hashes typed as 0x1234abcd
     pub fn rstb_edit_entry(&mut self, entry: String, val: String) -> Option<SendData> {
         let mut data = SendData::default();
         if let Some(rstb) = &mut self.opened_file.restbl {
             data.tab = "RSTB".to_string();
-            if let Some(_) = rstb.table.get(entry.clone()) {
-                //entry exists
-                data.status_text = format!("Modified: {}", &entry);
-            } else {
-                data.status_text = format!("Added: {}", &entry);
+            let val = match val.trim().parse::<u32>() {
+                Ok(val) => val,
+                Err(_) => {
+                    data.status_text = "Error: invalid size".to_string();
+                    data.tab = "ERROR".to_string();
+                    return Some(data);
+                }
+            };
+            let result = match parse_rstb_hash(&entry) {
+                Some(hash) => rstb.set_hash(hash, val).map(|old| old.is_some()),
+                None => {
+                    let exists = rstb.table.get(entry.clone()).is_some();
+                    rstb.set_named(&entry, val).map(|_| exists)
+                }
+            };
+            match result {
+                Ok(true) => data.status_text = format!("Modified: {}", &entry),
+                Ok(false) => data.status_text = format!("Added: {}", &entry),
+                Err(err) => {
+                    data.status_text = format!("Error: unable to set {}: {}", &entry, err);
+                    data.tab = "ERROR".to_string();
+                }
+            }
+            let collisions = rstb.collisions(&entry);
+            if parse_rstb_hash(&entry).is_none() && !collisions.is_empty() {
+                data.status_text.push_str(&format!(
+                    ", CRC32 collides with {} (stored by name)",
+                    collisions.join(", ")
+                ));
             }
-            rstb.table
-                .set(entry, val.parse::<u32>().expect("Failed to parse value"));
         } else {
             data.status_text = "Error: No RSTB opened".to_string();
             data.tab = "ERROR".to_string();
@@ -128,7 +197,13 @@ impl<'a> TotkBitsApp<'a> {
         let mut data = SendData::default();
         if let Some(rstb) = &mut self.opened_file.restbl {
             data.tab = "RSTB".to_string();
-            if let Some(_) = rstb.table.get(entry.clone()) {
+            if let Some(hash) = parse_rstb_hash(&entry) {
+                match rstb.remove_hash(hash) {
+                    Ok(Some(_)) => data.status_text = format!("Removed: {}", &entry),
+                    Ok(None) => data.status_text = format!("Error: entry absent in RSTB ({})", &entry),
+                    Err(err) => data.status_text = format!("Error: unable to remove {}: {}", &entry, err),
+                }
+            } else if let Some(_) = rstb.table.get(entry.clone()) {
                 //entry exists
                 data.status_text = format!("Removed: {}", &entry);
                 rstb.table.remove(entry.clone());
@@ -142,6 +217,101 @@ impl<'a> TotkBitsApp<'a> {
 
         Some(data)
     }
+
+    // Whole opened RSTB as yaml in the editor, saving from there parses it back into the table
+    pub fn rstb_to_text(&mut self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let rstb = self.opened_file.restbl.as_ref()?;
+        match rstb.to_text() {
+            Ok(text) => {
+                self.internal_file = None;
+                data.text = text;
+                data.tab = "YAML".to_string();
+                data.path = self.opened_file.path.clone();
+                data.get_file_label(TotkFileType::Restbl, Some(roead::Endian::Little));
+                data.status_text = format!("Opened {} as text", &self.opened_file.path.name);
+            }
+            Err(err) => {
+                data.status_text = format!("Error: unable to convert RSTB to text: {}", err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
+    // Entries of the opened RSTB that differ from vanilla of the same version, to yaml or json by extension
+    pub fn export_rstb_patch(&self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let rstb = self.opened_file.restbl.as_ref()?;
+        let patch = match RstbPatch::from_restbl(rstb) {
+            Ok(patch) => patch,
+            Err(err) => {
+                data.status_text = format!("Error: {}", err);
+                data.tab = "ERROR".to_string();
+                return Some(data);
+            }
+        };
+        if patch.entries.is_empty() {
+            data.status_text = "No RSTB entries differ from vanilla".to_string();
+            return Some(data);
+        }
+        let filters = vec![
+            ("YAML".to_string(), vec!["yml".to_string(), "yaml".to_string()]),
+            ("JSON".to_string(), vec!["json".to_string()]),
+        ];
+        let name = format!("{}_rstb_patch.yml", &patch.game_version);
+        let dest_file = self.zstd.ui.save_file("Export RSTB patch", &name, &filters)?;
+        match patch.save(&dest_file) {
+            Ok(_) => {
+                data.status_text = format!(
+                    "Exported {} RSTB entries to {}",
+                    patch.entries.len(),
+                    dest_file.to_string_lossy().replace("\\", "/")
+                );
+            }
+            Err(err) => {
+                data.status_text = format!("Error: unable to write {:?}: {}", &dest_file, err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
+    // Patch from any game version into the opened RSTB, conflicts are listed. Saving is left to the user
+    pub fn apply_rstb_patch(&mut self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let rstb = self.opened_file.restbl.as_mut()?;
+        let filters = vec![("RSTB patch".to_string(), vec!["yml".to_string(), "yaml".to_string(), "json".to_string()])];
+        let patch_path = self.zstd.ui.pick_file("Choose RSTB patch", &filters)?;
+        let patch = match RstbPatch::from_file(&patch_path) {
+            Ok(patch) => patch,
+            Err(err) => {
+                data.status_text = format!("Error: unable to read RSTB patch {:?}: {}", &patch_path, err);
+                data.tab = "ERROR".to_string();
+                return Some(data);
+            }
+        };
+        let target_version = rstb_game_version(&rstb.path.name).unwrap_or("unknown".to_string());
+        let report = match patch.apply(&mut rstb.table, &target_version) {
+            Ok(report) => report,
+            Err(err) => {
+                data.status_text = format!("Error: unable to apply RSTB patch {:?}: {}", &patch_path, err);
+                data.tab = "ERROR".to_string();
+                return Some(data);
+            }
+        };
+        if !report.conflicts.is_empty() {
+            let m = format!(
+                "{}\nVanilla sizes changed since the patch was made, kept the larger size:\n{}",
+                report.summary(),
+                report.conflicts_report(30)
+            );
+            self.zstd.ui.message("RSTB patch conflicts", &m);
+        }
+        data.tab = "RSTB".to_string();
+        data.status_text = report.summary();
+        Some(data)
+    }
     //END RSTB
 
     pub fn extract_folder_from_opened_sarc(&self, source_folder: String) -> Option<SendData> {
@@ -150,9 +320,7 @@ impl<'a> TotkBitsApp<'a> {
         if !is_sarc_root_path(&source_folder) {
             msg = format!("Extract SARC folder: \"{}\" to...", &source_folder);
         }
-        let dest_folder = FileDialog::new()
-            .set_title(&msg)
-            .pick_folder();
+        let dest_folder = self.zstd.ui.pick_folder(&msg);
         if let Some(dest_folder) = dest_folder {
             if let Some(pack) = &self.pack {
                 match pack.extract_folder(source_folder, &dest_folder) {
@@ -161,11 +329,7 @@ impl<'a> TotkBitsApp<'a> {
                     }
                     Err(e) => {
                         data.status_text = "Error: Failed to extract SARC".to_string();
-                        MessageDialog::new()
-                            .set_title("Error")
-                            .set_description(format!("Error: {}", e))
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .show();
+                        self.zstd.ui.message("Error", &format!("Error: {}", e));
                     }
                 }
 
@@ -192,28 +356,24 @@ impl<'a> TotkBitsApp<'a> {
                 is_reload = true;
                 if let Some(_) = opened.writer.get_file(&internal_path) {
                     //its a file
-                    if MessageDialog::new()
-                        .set_title("Remove file")
-                        .set_description(format!("The file:\n{}\nwill be removed. This operation cannot be reverted! Proceed?", &internal_path))
-                        .set_buttons(rfd::MessageButtons::YesNo)
-                        .show()
-                        == rfd::MessageDialogResult::No
-                    {
+                    if !self.zstd.ui.confirm(
+                        "Remove file",
+                        &format!("The file:\n{}\nwill be removed. Proceed?", &internal_path),
+                    ) {
                         return None;
                     }
-                    opened.writer.remove_file(&internal_path);
+                    let mut op = SarcOp::new(format!("remove {}", &internal_path));
+                    opened.remove_file_recorded(&mut op, &internal_path);
+                    opened.push_op(op);
                     data.status_text = format!("Removed {}", &internal_path);
                 } else {
                     //its a directory
-                    if MessageDialog::new()
-                    .set_title("Remove directory")
-                    .set_description(format!("All files from directory:\n{}\nwill be removed. This operation cannot be reverted! Proceed?", &internal_path))
-                    .set_buttons(rfd::MessageButtons::YesNo)
-                    .show()
-                    == rfd::MessageDialogResult::No
-                {
-                    return None;
-                }
+                    if !self.zstd.ui.confirm(
+                        "Remove directory",
+                        &format!("All files from directory:\n{}\nwill be removed. Proceed?", &internal_path),
+                    ) {
+                        return None;
+                    }
                     let mut to_remove: Vec<String> = Vec::new();
                     for file in opened.writer.files.keys() {
                         if file.starts_with(&internal_path) {
@@ -221,10 +381,12 @@ impl<'a> TotkBitsApp<'a> {
                         }
                     }
                     let mut i: usize = 0;
+                    let mut op = SarcOp::new(format!("remove {}", &internal_path));
                     for file in to_remove {
-                        opened.writer.remove_file(&file);
+                        opened.remove_file_recorded(&mut op, &file);
                         i += 1;
                     }
+                    opened.push_op(op);
                     data.status_text = format!("Removed {} files from {}", i, &internal_path);
                 }
             }
@@ -238,14 +400,178 @@ impl<'a> TotkBitsApp<'a> {
         None
     }
 
+    // Reverts (or applies again) the last add, remove, rename, replace or edit in the opened sarc
+    pub fn undo_sarc_op(&mut self, is_redo: bool) -> Option<SendData> {
+        let mut data = SendData::default();
+        let pack = self.pack.as_mut()?;
+        let opened = pack.opened.as_mut()?;
+        let description = if is_redo { opened.redo() } else { opened.undo() };
+        match description {
+            Some(description) => {
+                data.status_text = if is_redo {
+                    format!("Redone: {}", description)
+                } else {
+                    format!("Undone: {}", description)
+                };
+                pack.compare_and_reload();
+                data.get_sarc_paths(pack);
+            }
+            None => {
+                data.status_text = if is_redo { "Nothing to redo" } else { "Nothing to undo" }.to_string();
+            }
+        }
+        data.tab = "SARC".to_string();
+        Some(data)
+    }
+
+    // Vanilla bytes for a file or every file of a directory, removed entries included
+    pub fn restore_vanila_entry(&mut self, internal_path: String) -> Option<SendData> {
+        let mut data = SendData::default();
+        let pack = self.pack.as_mut()?;
+        match pack.restore_vanila(&internal_path) {
+            Ok((restored, not_vanila)) => {
+                data.status_text = match (restored.len(), not_vanila.len()) {
+                    (0, _) => format!("No vanilla version of {} found", &internal_path),
+                    (1, 0) => format!("Restored {} from vanilla", &restored[0]),
+                    (r, 0) => format!("Restored {} files from vanilla", r),
+                    (r, n) => format!("Restored {} files from vanilla, {} without vanilla version", r, n),
+                };
+                data.get_sarc_paths(pack);
+            }
+            Err(err) => {
+                data.status_text = format!("Error: unable to restore {}: {}", &internal_path, err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
+    // Removes the entries of the opened sarc identical to vanilla, after listing them
+    pub fn minimize_sarc(&mut self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let pack = self.pack.as_mut()?;
+        let preview = pack.minimize(true);
+        if preview.entries.is_empty() {
+            data.status_text = "No entries identical to vanilla".to_string();
+            return Some(data);
+        }
+        let m = format!(
+            "{} entries ({}) are identical to vanilla and will be removed:\n{}\nProceed?",
+            preview.entries.len(),
+            format_size(preview.bytes_saved),
+            preview.listing(30)
+        );
+        if !self.zstd.ui.confirm("Minimize", &m) {
+            return None;
+        }
+        let report = pack.minimize(false);
+        data.status_text = report.summary();
+        data.get_sarc_paths(pack);
+        Some(data)
+    }
+
+    // Deletes the files of a mod folder identical to the romfs dump, after listing them
+    pub fn minimize_mod_folder(&self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let folder = self.zstd.ui.pick_folder("Choose mod folder to minimize")?;
+        let result = minimize_folder(&self.zstd, &folder, true).and_then(|preview| {
+            if preview.entries.is_empty() {
+                return Ok(preview);
+            }
+            let m = format!(
+                "{} files ({}) are identical to vanilla and will be deleted:\n{}\nProceed?",
+                preview.entries.len(),
+                format_size(preview.bytes_saved),
+                preview.listing(30)
+            );
+            if !self.zstd.ui.confirm("Minimize", &m) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+            }
+            minimize_folder(&self.zstd, &folder, false)
+        });
+        match result {
+            Ok(report) if report.entries.is_empty() => {
+                data.status_text = "No files identical to vanilla".to_string();
+            }
+            Ok(report) => {
+                if !report.failures.is_empty() {
+                    self.zstd.ui.message("Failed files", &report.failures.report(30));
+                }
+                data.status_text = report.summary();
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => return None,
+            Err(err) => {
+                data.status_text = format!("Error: {}", err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
+    // RSTB sizes for what the mod adds or changes, written to the mod's own table
+    pub fn regenerate_mod_rstb(&self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let folder = self.zstd.ui.pick_folder("Choose mod romfs folder")?;
+        let result = regenerate_rstb(self.zstd.clone(), &folder, true).and_then(|preview| {
+            if preview.is_empty() {
+                return Ok(preview);
+            }
+            let m = format!(
+                "{}\n{}\nWrite to {}?",
+                preview.summary(),
+                preview.listing(30),
+                &preview.output
+            );
+            if !self.zstd.ui.confirm("Regenerate RSTB", &m) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+            }
+            regenerate_rstb(self.zstd.clone(), &folder, false)
+        });
+        match result {
+            Ok(report) if report.is_empty() => {
+                data.status_text = format!("RSTB up to date, {} entries already large enough", report.untouched.len());
+            }
+            Ok(report) => data.status_text = report.summary(),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => return None,
+            Err(err) => {
+                data.status_text = format!("Error: {}", err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
+    // Added, modified and removed entries of the opened sarc to json or markdown, by extension
+    pub fn export_pack_diff_report(&self) -> Option<SendData> {
+        let mut data = SendData::default();
+        let pack = self.pack.as_ref()?;
+        let name = pack.opened.as_ref().map(|o| o.path.stem.clone()).unwrap_or_default();
+        let filters = vec![
+            ("Markdown".to_string(), vec!["md".to_string()]),
+            ("JSON".to_string(), vec!["json".to_string()]),
+        ];
+        let dest_file = self
+            .zstd
+            .ui
+            .save_file("Export diff report", &format!("{}_diff.md", name), &filters)?;
+        let report = PackDiffReport::from_pack(pack, self.zstd.clone());
+        let is_json = dest_file.to_string_lossy().to_lowercase().ends_with(".json");
+        let text = if is_json { report.to_json() } else { report.to_markdown() };
+        match write_string_to_file(&dest_file.to_string_lossy(), &text) {
+            Ok(_) => {
+                data.status_text = format!("Exported diff report ({}) to {}", report.summary(), dest_file.to_string_lossy().replace("\\", "/"));
+            }
+            Err(err) => {
+                data.status_text = format!("Error: unable to write {:?}: {}", &dest_file, err);
+                data.tab = "ERROR".to_string();
+            }
+        }
+        Some(data)
+    }
+
     pub fn close_all_click(&mut self) -> Option<SendData> {
         if self.zstd.totk_config.close_all_prompt
-            && MessageDialog::new()
-                .set_title("Close all")
-                .set_description("All currently opened files will be closed. Proceed?")
-                .set_buttons(rfd::MessageButtons::YesNo)
-                .show()
-                == rfd::MessageDialogResult::No
+            && !self.zstd.ui.confirm("Close all", "All currently opened files will be closed. Proceed?")
         {
             return None;
         }
@@ -266,6 +592,7 @@ impl<'a> TotkBitsApp<'a> {
         zstd: Arc<TotkZstd>,
         dest_file: &str,
     ) -> Option<Vec<u8>> {
+        let zs_dict = self.opened_file.zs_dict;
         get_binary_by_filetype(
             self.opened_file.file_type,
             text,
@@ -273,6 +600,7 @@ impl<'a> TotkBitsApp<'a> {
             zstd.clone(),
             dest_file,
             &mut self.opened_file,
+            zs_dict,
         )
     }
 
@@ -285,7 +613,7 @@ impl<'a> TotkBitsApp<'a> {
         );
         dialog.name = Some(Pathlib::new(internal_path.clone()).name);
         dialog.filters_from_path(&internal_path);
-        let path = dialog.show();
+        let path = dialog.show(self.zstd.ui.as_ref());
         if path.is_empty() {
             return None;
         }
@@ -326,11 +654,12 @@ impl<'a> TotkBitsApp<'a> {
             if let Some(opened) = &mut pack.opened {
                 is_reload = true;
                 //file is in sarc
+                let mut op = SarcOp::new(format!("rename {} to {}", &p1.name, &p2.name));
                 if let Some(rawdata) = opened.writer.get_file(&internal_path) {
                     let rawdata_backup = rawdata.clone();
                     let new_path = format!("{}/{}", &p1.parent, &p2.name);
-                    opened.writer.remove_file(&internal_path);
-                    opened.writer.add_file(&new_path, rawdata_backup);
+                    opened.remove_file_recorded(&mut op, &internal_path);
+                    opened.add_file_recorded(&mut op, &new_path, rawdata_backup);
                     data.status_text = format!("Renamed {} to {}", &p1.name, &p2.name);
                 } else {
                     //assuming the node is a directory
@@ -362,8 +691,8 @@ impl<'a> TotkBitsApp<'a> {
                             }
                             new_file_path = new_file_path.replace("//", "/");
                             println!("{} -> {}", &file, &new_file_path);
-                            opened.writer.remove_file(&file);
-                            opened.writer.add_file(&new_file_path, rawdata_backup);
+                            opened.remove_file_recorded(&mut op, &file);
+                            opened.add_file_recorded(&mut op, &new_file_path, rawdata_backup);
                             i += 1;
                         }
                     }
@@ -372,6 +701,7 @@ impl<'a> TotkBitsApp<'a> {
                         &p1.name, &p2.name, i
                     );
                 }
+                opened.push_op(op);
             }
             if is_reload {
                 pack.compare_and_reload();
@@ -399,9 +729,13 @@ impl<'a> TotkBitsApp<'a> {
         }
         let files = list_files_recursively(&path_var.full_path);
         let files_len = files.len();
+        let pack = self.pack.as_mut()?;
+        let opened = pack.opened.as_mut()?;
         if files_len == 0 {
             data.status_text = format!("No files found in {}", &path_var.full_path);
         } else {
+            //one operation for the whole folder, so a single undo removes it
+            let mut op = SarcOp::new(format!("add folder {}", &dirname));
             for file in files {
                 let file_path = Pathlib::new(&file);
                 let mut file_path_to_add = file_path.full_path[path_root_len..].to_string();
@@ -412,13 +746,16 @@ impl<'a> TotkBitsApp<'a> {
                 if new_internal_path.starts_with("/") { //if adding to root
                     new_internal_path = new_internal_path[1..].to_string();
                 }
-                let _ = self.add_internal_file_from_path(new_internal_path, file, true);
+                match fs::read(&file) {
+                    Ok(buffer) => opened.add_file_recorded(&mut op, &new_internal_path, buffer),
+                    Err(err) => println!("ERROR: unable to add {} to sarc path {}: {:?}", &file, &new_internal_path, err),
+                }
             }
+            opened.push_op(op);
             data.status_text = format!("Added {} files to {}", files_len, &int_path.full_path);
         }
-        if let Some(pack) = &mut self.pack {
-            data.get_sarc_paths(pack);
-        }
+        pack.compare_and_reload();
+        data.get_sarc_paths(pack);
         Some(data)
         // None
     }
@@ -454,13 +791,7 @@ impl<'a> TotkBitsApp<'a> {
                             "{}\nalready exists in {}. Proceed?",
                             &internal_path, &opened.path.name
                         );
-                        if MessageDialog::new()
-                            .set_title("File already exists")
-                            .set_description(m)
-                            .set_buttons(rfd::MessageButtons::YesNo)
-                            .show()
-                            == rfd::MessageDialogResult::No
-                        {
+                        if !self.zstd.ui.confirm("File already exists", &m) {
                             return None;
                         }
                     }
@@ -470,9 +801,10 @@ impl<'a> TotkBitsApp<'a> {
                 let mut f_handle = fs::File::open(&path).ok()?;
                 let mut buffer: Vec<u8> = Vec::new();
                 f_handle.read_to_end(&mut buffer).ok()?;
-                opened
-                    .writer
-                    .add_file(&internal_path.replace("\\", "/"), buffer);
+                let internal_path = internal_path.replace("\\", "/");
+                let mut op = SarcOp::new(format!("add/replace {}", &internal_path));
+                opened.add_file_recorded(&mut op, &internal_path, buffer);
+                opened.push_op(op);
                 data.status_text = format!("Added/replaced: {}", &internal_path);
             }
             if is_reload {
@@ -518,7 +850,7 @@ impl<'a> TotkBitsApp<'a> {
             println!("Nothing is opened, nothing to save");
             return None;
         }
-        let dest_file = dialog.show();
+        let dest_file = dialog.show(self.zstd.ui.as_ref());
         if !dest_file.is_empty() && !check_if_save_in_romfs(&dest_file, self.zstd.clone()) {
             match save_data.tab.as_str() {
                 "YAML" => {
@@ -554,7 +886,12 @@ impl<'a> TotkBitsApp<'a> {
                 "SARC" => {
                     let mut is_reload = false;
                     if let Some(pack) = &mut self.pack {
-                        if let Some(opened) = &mut pack.opened {
+                        //nested archives are saved inside the file opened from disk
+                        if let Err(err) = pack.commit_all() {
+                            data.status_text = format!("Error: Failed to save nested SARC: {}", err);
+                            return Some(data);
+                        }
+                        if let Some(opened) = pack.root_pack_mut() {
                             match opened.save(dest_file.clone()) {
                                 Ok(_) => {
                                     is_reload = true;
@@ -621,6 +958,7 @@ impl<'a> TotkBitsApp<'a> {
                         self.zstd.clone(),
                         &path,
                         &mut self.opened_file,
+                        internal_file.zs_dict,
                     )?;
                     if rawdata.is_empty() {
                         data.status_text =
@@ -629,7 +967,9 @@ impl<'a> TotkBitsApp<'a> {
                         println!("{:?}", &data);
                         return Some(data);
                     } else {
-                        opened.writer.add_file(path, rawdata);
+                        let mut op = SarcOp::new(format!("edit {}", &internal_file.path.name));
+                        opened.add_file_recorded(&mut op, path, rawdata);
+                        opened.push_op(op);
                         is_reload = true;
                         data.tab = "YAML".to_string();
                         data.status_text = format!(
@@ -645,6 +985,7 @@ impl<'a> TotkBitsApp<'a> {
             }
         } else {
             let fullpath = self.opened_file.path.full_path.clone();
+            let zs_dict = self.opened_file.zs_dict;
             let rawdata: Vec<u8> = get_binary_by_filetype(
                 self.opened_file.file_type,
                 text,
@@ -652,6 +993,7 @@ impl<'a> TotkBitsApp<'a> {
                 self.zstd.clone(),
                 &fullpath,
                 &mut self.opened_file,
+                zs_dict,
             )?;
             if rawdata.is_empty() {
                 data.status_text =
@@ -689,7 +1031,12 @@ impl<'a> TotkBitsApp<'a> {
             }
             "SARC" => {
                 if let Some(pack) = &mut self.pack {
-                    if let Some(opened) = &mut pack.opened {
+                    //nested archives are saved inside the file opened from disk
+                    if let Err(err) = pack.commit_all() {
+                        data.status_text = format!("Error: Failed to save nested SARC: {}", err);
+                        return Some(data);
+                    }
+                    if let Some(opened) = pack.root_pack_mut() {
                         if !check_if_save_in_romfs(&opened.path.full_path, self.zstd.clone()) {
                             opened.reload();
                             match opened.save_default() {
@@ -753,7 +1100,9 @@ impl<'a> TotkBitsApp<'a> {
                     new_path.push(format!("new_{}.byml", i));
                     let dest_file = new_path.to_string_lossy().to_string().replace("\\", "/");
                     if !opened.writer.files.contains_key(&dest_file) {
-                        opened.writer.add_file(&dest_file, raw_data.clone());
+                        let mut op = SarcOp::new(format!("add {}", &dest_file));
+                        opened.add_file_recorded(&mut op, &dest_file, raw_data.clone());
+                        opened.push_op(op);
                         data.status_text = format!("Added {}", &dest_file);
                         is_reload = true;
                         break;
@@ -770,10 +1119,59 @@ impl<'a> TotkBitsApp<'a> {
         Some(data)
     }
 
+    // SendData for the archive shown in the sarc tab, label lists every archive above it
+    fn nested_sarc_data(pack: &PackComparer) -> SendData {
+        let mut data = SendData::default();
+        data.tab = "SARC".to_string();
+        data.get_sarc_paths(pack);
+        if let Some(opened) = &pack.opened {
+            let e_s = if opened.endian == roead::Endian::Little { " [LE]" } else { " [BE]" };
+            let yaz0_s = if opened.is_yaz0 { " [Yaz0] " } else { " " };
+            data.path = opened.path.clone();
+            data.file_label = format!("{}{}[SARC]{}", pack.breadcrumbs().join(" > "), yaz0_s, e_s);
+        }
+        data
+    }
+
+    // Opens a sarc stored in the opened one, None if the file is not an archive
+    pub fn open_nested_sarc(&mut self, path: &str) -> Option<SendData> {
+        let pack = self.pack.as_mut()?;
+        let raw_data = pack.opened.as_ref()?.sarc.get_data(path)?;
+        if !(is_sarc(raw_data) || raw_data.starts_with(b"Yaz0") || is_zstd(raw_data)) {
+            return None;
+        }
+        if let Err(err) = pack.open_nested(path) {
+            println!("{} is not a nested sarc: {:?}", path, err);
+            return None;
+        }
+        self.internal_file = None;
+        let mut data = Self::nested_sarc_data(pack);
+        data.status_text = format!("Opened {}", pack.breadcrumbs().join(" > "));
+        Some(data)
+    }
+
+    // Back to the archive at `level` of the breadcrumbs, 0 is the file opened from disk
+    pub fn close_nested_sarc(&mut self, level: usize) -> Option<SendData> {
+        let pack = self.pack.as_mut()?;
+        if let Err(err) = pack.close_nested_to(level) {
+            let mut data = SendData::default();
+            data.tab = "ERROR".to_string();
+            data.status_text = format!("Error: unable to close nested sarc: {}", err);
+            return Some(data);
+        }
+        self.internal_file = None;
+        let mut data = Self::nested_sarc_data(pack);
+        data.status_text = format!("Opened {}", pack.breadcrumbs().join(" > "));
+        Some(data)
+    }
+
     pub fn edit_internal_file(&mut self, path: String) -> Option<SendData> {
         if path.is_empty() || !path.contains(".") {
             return None;
         }
+        if let Some(data) = self.open_nested_sarc(&path) {
+            return Some(data);
+        }
         let mut data = SendData::default();
         if let Some(pack) = &mut self.pack {
             if let Some(opened) = &mut pack.opened {
@@ -819,6 +1217,7 @@ impl<'a> TotkBitsApp<'a> {
         if let Some(pack) = &mut self.pack {
             data.get_sarc_paths(pack);
             data.sarc_paths.paths = Vec::new();
+            data.sarc_paths.removed_paths = Vec::new(); //only matches are listed
             if let Some(opened) = &mut pack.opened {
                 for file in opened.sarc.files() {
                     if let Some((_, text)) =
@@ -904,10 +1303,7 @@ impl<'a> TotkBitsApp<'a> {
     }
 
     pub fn open(&mut self) -> Option<SendData> {
-        if let Some(file) = FileDialog::new()
-            .set_title("Choose file to open")
-            .pick_file()
-        {
+        if let Some(file) = self.zstd.ui.pick_file("Choose file to open", &Vec::new()) {
             return self.open_from_path(file.to_string_lossy().to_string().replace("\\", "/"));
         }
         None
@@ -1039,6 +1435,7 @@ pub struct InternalFile<'a> {
     pub text: Option<String>,
     pub aamp: Option<String>,
     pub esetb: Option<Esetb<'a>>,
+    pub zs_dict: Option<ZsDicType>,
 }
 
 impl Default for InternalFile<'_> {
@@ -1052,6 +1449,7 @@ impl Default for InternalFile<'_> {
             text: None,
             aamp: None,
             esetb: None,
+            zs_dict: None,
         }
     }
 }
@@ -1069,6 +1467,7 @@ impl InternalFile<'_> {
             text: None,
             aamp: None,
             esetb: None,
+            zs_dict: None,
         }
     }
 }
@@ -7,18 +7,24 @@ use std::fs::File;
 use std::io;
 use std::io::BufRead;
 use std::io::BufReader;
-use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
-use flate2::read::ZlibDecoder;
+use std::sync::Arc;
 //use roead::byml::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tauri::api::version;
 use updater::TotkbitsVersion::TotkbitsVersion;
 
 use crate::file_format::Pack::PackFile;
+use crate::Compression::{CompressionPolicy, DEFAULT_COMPRESSION_LEVEL, DEFAULT_COMPRESSION_RULES};
+use crate::Zstd::ZSDIC_FILES;
+use crate::Interaction::UserInteraction;
+use crate::Plugins::{PluginRegistry, XLINK_PLUGIN};
+use crate::RomfsIndex::RomfsIndex;
+use crate::RomfsIndexer::INDEX_DIR;
 use crate::Settings::makedirs;
+use crate::Settings::find_in_path;
+use crate::Settings::{DOTNET_WRAPPER_DLL, DOTNET_WRAPPER_EXE, PYTHON_EXE, PYTHON_SCRIPT};
 use crate::Settings::write_string_to_file;
 use crate::Settings::Pathlib;
 
@@ -44,7 +50,16 @@ pub struct TotkConfig {
     pub available_themes: Vec<String>,
     #[serde(skip)]
     pub config_path: String,
+    #[serde(skip)]
+    pub index: Arc<RomfsIndex>, //lookup databases, loaded on first use
     pub botw_romfs_path: String,
+    pub xlink_plugin_path: String,
+    pub python_exe: String,
+    pub python_script: String,
+    pub dotnet_wrapper_path: String,
+    pub compression_level: i32,
+    pub compression_rules: String,
+    pub zsdic_path: String,
 }
 
 impl Default for TotkConfig {
@@ -64,15 +79,23 @@ impl Default for TotkConfig {
             game_versions: (100..130).rev().map(|e| e.to_string()).collect(),
             available_themes: vec!["vs".into(), "vs-dark".into(), "hc-black".into(), "hc-light".into()],
             config_path: String::new(),
+            index: Arc::new(RomfsIndex::default()),
             botw_romfs_path: String::new(),
+            xlink_plugin_path: String::new(),
+            python_exe: String::new(),
+            python_script: String::new(),
+            dotnet_wrapper_path: String::new(),
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            compression_rules: DEFAULT_COMPRESSION_RULES.to_string(),
+            zsdic_path: String::new(),
         }
     }
 }
 
 impl TotkConfig {
     
-    pub fn safe_new() -> io::Result<TotkConfig> {
-        match Self::new() {
+    pub fn safe_new(ui: &dyn UserInteraction) -> io::Result<TotkConfig> {
+        match Self::new(ui) {
             Ok(conf) => {
                 Ok(conf)
             },
@@ -88,11 +111,36 @@ impl TotkConfig {
         }
     }
 
+    // Full romfs dump: zstd and comparing with vanilla files
     pub fn is_valid(&self) -> bool {
         !self.romfs.is_empty() && Self::check_for_zsdic(&self.romfs)
     }
 
-    pub fn from_toml() -> io::Result<TotkConfig> {
+    // Zstd dictionaries available, either from the romfs or from a standalone ZsDic
+    pub fn has_zsdic(&self) -> bool {
+        self.get_zsdic_path().is_some()
+    }
+
+    // Configured standalone ZsDic (ZsDic.pack.zs or a folder with the .zsdic files) first, then the romfs one
+    pub fn get_zsdic_path(&self) -> Option<PathBuf> {
+        if !self.zsdic_path.is_empty() && Self::check_standalone_zsdic(&self.zsdic_path) {
+            return Some(PathBuf::from(&self.zsdic_path));
+        }
+        if self.is_valid() {
+            return Some(PathBuf::from(&self.romfs).join("Pack/ZsDic.pack.zs"));
+        }
+        None
+    }
+
+    pub fn check_standalone_zsdic<P: AsRef<Path>>(path: P) -> bool {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return ZSDIC_FILES.iter().all(|name| path.join(name).is_file());
+        }
+        path.is_file()
+    }
+
+    pub fn from_toml(ui: &dyn UserInteraction) -> io::Result<TotkConfig> {
         let mut conf = Self::default();
         conf.get_game_version().unwrap_or_default();//no point in handling error here
         //get config path
@@ -105,11 +153,7 @@ impl TotkConfig {
                 Ok(s) => conf_str = s,
                 Err(e) => {
                     let e = format!("Unable to read config file:\n{}", e);
-                    rfd::MessageDialog::new()
-                        .set_buttons(rfd::MessageButtons::Ok)
-                        .set_title("Error")
-                        .set_description(&e)
-                        .show();
+                    ui.message("Error", &e);
                     // return Err(io::Error::new(io::ErrorKind::NotFound, e));
                 }
             }
@@ -119,10 +163,10 @@ impl TotkConfig {
         Ok(conf)
     }
 
-    pub fn new() -> io::Result<TotkConfig> {
+    pub fn new(ui: &dyn UserInteraction) -> io::Result<TotkConfig> {
         let mut conf = Self::default();
-        if let Ok(_conf) = Self::from_toml() {
-            if _conf.is_valid() {
+        if let Ok(_conf) = Self::from_toml(ui) {
+            if _conf.has_zsdic() {
                 return Ok(_conf);
             }
             conf = _conf;
@@ -131,9 +175,9 @@ impl TotkConfig {
 
         conf.get_game_version().unwrap_or_default();//no point in handling error here
         
-        if !conf.is_valid() {
+        if !conf.has_zsdic() {
             //unable to find romfs path, get it from NX editor or user input
-            conf.update_romfs_path()?;//throws error if not found
+            conf.update_romfs_path(ui)?;//throws error if not found
         }
 
         conf.save()?;
@@ -182,6 +226,15 @@ impl TotkConfig {
         self.rotation_deg = get_bool(&json_data, "Rotation in degrees", self.rotation_deg);
         self.romfs = get_string(&json_data, "romfs");
         self.botw_romfs_path = get_string(&json_data, "BOTW WIIU path (optional)");
+        self.xlink_plugin_path = get_string(&json_data, "Xlink plugin path (optional)");
+        self.python_exe = get_string(&json_data, "Python path (optional)");
+        self.python_script = get_string(&json_data, "Python script path (optional)");
+        self.dotnet_wrapper_path = get_string(&json_data, "DotNet wrapper path (optional)");
+        self.zsdic_path = get_string(&json_data, "ZsDic path (optional)").replace("\\", "/");
+        self.compression_level = get_i64(&json_data, "Compression level", self.compression_level as i64) as i32;
+        if let Some(rules) = json_data.get("Compression rules").and_then(|v| v.as_str()) {
+            self.compression_rules = rules.to_string(); //absent in older configs, keep the defaults then
+        }
     
         self.yaml_max_inl = self.yaml_max_inl.max(MIN_INLINE_BYML_ITEMS).min(MAX_INLINE_BYML_ITEMS);
         // println!("Updated config from json data {:?}", self);
@@ -203,6 +256,13 @@ impl TotkConfig {
                 "Prompt on close all": self.close_all_prompt,
                 "Rotation in degrees": self.rotation_deg,
                 "BOTW WIIU path (optional)": self.botw_romfs_path,
+                "Xlink plugin path (optional)": self.xlink_plugin_path,
+                "Python path (optional)": self.python_exe,
+                "Python script path (optional)": self.python_script,
+                "DotNet wrapper path (optional)": self.dotnet_wrapper_path,
+                "ZsDic path (optional)": self.zsdic_path,
+                "Compression level": self.compression_level,
+                "Compression rules": self.compression_rules,
             })
         )
     }
@@ -262,7 +322,17 @@ impl TotkConfig {
         if !self.game_version.is_empty() {
             res.push_str(&format!("# Detected game version: {}\n", self.game_version));
         }
-        res.push_str(&format!("# Byml inline container max count must be between {} and {}\n#\n", MIN_INLINE_BYML_ITEMS, MAX_INLINE_BYML_ITEMS));
+        res.push_str(&format!("# Byml inline container max count must be between {} and {}\n", MIN_INLINE_BYML_ITEMS, MAX_INLINE_BYML_ITEMS));
+        res.push_str(&format!("# Xlink plugin path: leave empty to use {}\n", PluginRegistry::default_path(XLINK_PLUGIN)));
+        res.push_str("# Python and DotNet wrapper paths: leave empty to autodetect (bundled, then PATH)\n");
+        res.push_str(&format!("# Detected python: {}\n", self.get_python_exe()));
+        res.push_str(&format!("# Detected DotNet wrapper: {}\n", self.get_dotnet_wrapper().join(" ")));
+        res.push_str(&format!("# ZsDic path: ZsDic.pack.zs or a folder with {}, used instead of the romfs one.\n", ZSDIC_FILES.join(", ")));
+        res.push_str("#   Enough for zstd without a romfs dump, comparing with vanilla files still needs the romfs\n");
+        res.push_str("# Compression rules: target=dictionary[:level] separated by ;, first match wins\n");
+        res.push_str("#   target: file name glob (*.pack), path glob (Mals/*) or file type ([Sarc])\n");
+        res.push_str("#   dictionary: zs, pack, bcett, empty or auto. Dictionary of an opened .zs file is always kept\n");
+        res.push_str(&format!("#   default: {}\n#\n", DEFAULT_COMPRESSION_RULES));
         if let Ok(exe_path) = env::current_exe() {
             if let Some(cwd_path) = exe_path.parent() {
                 res.push_str(&format!("# Current working directory: {}\n", cwd_path.to_string_lossy().to_string().replace("\\", "/")));
@@ -276,8 +346,8 @@ impl TotkConfig {
         Ok(())
     }
 
-    pub fn update_romfs_path(&mut self) -> io::Result<()> {
-        if self.update_romfs_from_NX().is_err() && self.update_romfs_from_input().is_err() {
+    pub fn update_romfs_path(&mut self, ui: &dyn UserInteraction) -> io::Result<()> {
+        if self.update_romfs_from_NX().is_err() && self.update_romfs_from_input(ui).is_err() {
             Err(io::Error::new(io::ErrorKind::NotFound, "Unable to get romfs path from NX editor or user input"))
         } else {
             Ok(())
@@ -301,10 +371,9 @@ impl TotkConfig {
         }
         return Err(io::Error::new(io::ErrorKind::NotFound, "Unable to parse nx editor config"));
     }
-    pub fn update_romfs_from_input(&mut self) -> io::Result<()> {
-        let mut chosen = rfd::FileDialog::new()
-            .set_title("Choose Tears of The Kingdom path to dumped romfs")
-            .pick_folder()
+    pub fn update_romfs_from_input(&mut self, ui: &dyn UserInteraction) -> io::Result<()> {
+        let mut chosen = ui
+            .pick_folder("Choose Tears of The Kingdom path to dumped romfs")
             .unwrap_or_default();
         let res = chosen.to_string_lossy().to_string().replace("\\", "/");
         if !Self::check_for_zsdic(&res) {
@@ -313,11 +382,7 @@ impl TotkConfig {
             // let e = if !res.is_empty() {format!("Invalid romfs path! ZsDic.pack.zs not found:\n{}", chosen.to_string_lossy().to_string().replace("\\", "/")) } else {"No romfs path selected. ZSTD disabled and comparison support limited".to_string()};
             let e = if !res.is_empty() {format!("Invalid romfs path! ZsDic.pack.zs not found:\n{}", chosen.to_string_lossy().to_string().replace("\\", "/")) } else {"".to_string()};
             if !e.is_empty() {
-                rfd::MessageDialog::new()
-                    .set_buttons(rfd::MessageButtons::Ok)
-                    .set_title(&t)
-                    .set_description(&e)
-                    .show();
+                ui.message(&t, &e);
             }
             return Err(io::Error::new(io::ErrorKind::NotFound, e));
         }
@@ -349,12 +414,28 @@ impl TotkConfig {
 
     //FIND VANLA FILE IN ROMFS
     pub fn find_vanila_file_in_romfs<P: AsRef<Path>>(&self, path: P) -> io::Result<String> {
-        //parse json
-        let json_zlibdata = fs::read("bin/totk_filename_to_localpath.bin")?;
-        let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
-        let mut json_str = String::new();
-        decoder.read_to_string(&mut json_str)?;
-        let res: HashMap<String, String> = serde_json::from_str(&json_str)?;
+        let res = self.get_filename_to_localpath_data()?;
+        self.find_vanila_file_in_romfs_with(&res, path)
+    }
+
+    //File name -> path in romfs
+    pub fn get_filename_to_localpath_data(&self) -> io::Result<Arc<HashMap<String, String>>> {
+        self.index.filename_to_localpath(self)
+    }
+
+    //Database indexed from this romfs (bin/index/<game version>/), else the prebuilt one in bin/
+    pub fn get_index_db_path(&self, name: &str) -> PathBuf {
+        if !self.game_version.is_empty() {
+            let indexed = PathBuf::from(INDEX_DIR).join(&self.game_version).join(name);
+            if indexed.exists() {
+                return indexed;
+            }
+        }
+        PathBuf::from("bin").join(name)
+    }
+
+    //Same as above with the data already parsed, for lookups of many files
+    pub fn find_vanila_file_in_romfs_with<P: AsRef<Path>>(&self, res: &HashMap<String, String>, path: P) -> io::Result<String> {
         //get filename (key)
         let filename = Pathlib::new(&path).name;
         let mut filenames: Vec<String> = vec![];
@@ -430,7 +511,59 @@ impl TotkConfig {
         self.get_path(&format!("Mals/{}", name))
     }
 
-    
+    //COMPRESSION
+    pub fn compression_policy(&self) -> io::Result<CompressionPolicy> {
+        CompressionPolicy::new(&self.compression_rules, self.compression_level)
+    }
+
+    //EXTERNAL CONVERTERS
+    //configured path first, then the bundled one, then whatever is on PATH
+    pub fn get_python_exe(&self) -> String {
+        if !self.python_exe.is_empty() {
+            return self.python_exe.replace("\\", "/");
+        }
+        if Path::new(PYTHON_EXE).is_file() {
+            return PYTHON_EXE.to_string();
+        }
+        for name in ["python3", "python"] {
+            if let Some(found) = find_in_path(name) {
+                return found;
+            }
+        }
+        PYTHON_EXE.to_string()
+    }
+
+    pub fn get_python_script(&self) -> String {
+        if !self.python_script.is_empty() {
+            return self.python_script.replace("\\", "/");
+        }
+        PYTHON_SCRIPT.to_string()
+    }
+
+    //program followed by its arguments, framework dependent builds (.dll) run through dotnet
+    pub fn get_dotnet_wrapper(&self) -> Vec<String> {
+        let run_dll = |dll: &str| find_in_path("dotnet").map(|dotnet| vec![dotnet, dll.to_string()]);
+        if !self.dotnet_wrapper_path.is_empty() {
+            let path = self.dotnet_wrapper_path.replace("\\", "/");
+            if path.to_ascii_lowercase().ends_with(".dll") {
+                if let Some(cmd) = run_dll(&path) {
+                    return cmd;
+                }
+            }
+            return vec![path];
+        }
+        if Path::new(DOTNET_WRAPPER_EXE).is_file() {
+            return vec![DOTNET_WRAPPER_EXE.to_string()];
+        }
+        if Path::new(DOTNET_WRAPPER_DLL).is_file() {
+            if let Some(cmd) = run_dll(DOTNET_WRAPPER_DLL) {
+                return cmd;
+            }
+        }
+        vec![DOTNET_WRAPPER_EXE.to_string()]
+    }
+
+    #[cfg(windows)]
     pub fn get_config_root_path() -> String {
         //save config in localappdata, if not possible save in appdata, if not possible save in exe path
         if let Ok(appdata) = env::var("LOCALAPPDATA") {
@@ -439,6 +572,24 @@ impl TotkConfig {
         if let Ok(appdata) = env::var("APPDATA") {
             return appdata;
         }
+        Self::get_exe_dir_fallback()
+    }
+
+    #[cfg(not(windows))]
+    pub fn get_config_root_path() -> String {
+        //XDG base dirs: $XDG_CONFIG_HOME, then ~/.config, if not possible save in exe path
+        if let Ok(xdg) = env::var("XDG_CONFIG_HOME") {
+            if !xdg.is_empty() {
+                return xdg;
+            }
+        }
+        if let Ok(home) = env::var("HOME") {
+            return format!("{}/.config", home);
+        }
+        Self::get_exe_dir_fallback()
+    }
+
+    fn get_exe_dir_fallback() -> String {
         if let Ok(exe_path) = env::current_exe() {
             if let Some(cwd_path) = exe_path.parent() {
                 return cwd_path.to_string_lossy().to_string().replace("\\", "/");
@@ -1,28 +1,26 @@
+use crate::file_format::BinTextFile::is_banc_path;
 use crate::file_format::Pack::PackFile;
+use crate::Compression::CompressionPolicy;
+use crate::Interaction::UserInteraction;
 use crate::Open_and_Save::get_string_from_data;
+use crate::Plugins::PluginRegistry;
 use crate::TotkConfig::TotkConfig;
 use digest::Digest;
-use flate2::read::ZlibDecoder;
-use libloading::{Library, Symbol};
 use roead::sarc::*;
 use sha2::Sha256;
-use zstd::zstd_safe::zstd_sys::{
-    ZSTD_CCtx, ZSTD_CDict_s, ZSTD_compressBound, ZSTD_compress_usingCDict, ZSTD_createCCtx, ZSTD_createCDict, ZSTD_isError
-};
 
 use std::collections::HashMap;
 
 
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 //use zstd::zstd_safe::CompressionLevel;
 use std::{env, fs};
-use std::io::{self, Cursor, Read, Write};
+use std::io::{self, Cursor, Read};
 use zstd::dict::{DecoderDictionary, EncoderDictionary};
-use zstd::{stream::decode_all, stream::Decoder, stream::Encoder};
-
-pub const  COMPRESSION_LEVEL: i32 = 16;
+use zstd::zstd_safe::{self, CCtx};
+use zstd::{stream::decode_all, stream::Decoder};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TotkFileType {
@@ -40,105 +38,30 @@ pub enum TotkFileType {
     Evfl,
     Xlink,
     Text,
+    Hex,
     Other,
     //SMO
     SmoSaveFile,
     None,
 }
 
-pub struct ZstdCppCompressor {
-    pub zs_cpp: *mut ZSTD_CDict_s,
-    pub bcett_cpp: *mut ZSTD_CDict_s,
-    pub packzs_cpp: *mut ZSTD_CDict_s,
-    pub empty_cpp: *mut ZSTD_CDict_s,
-    pub cctx: *mut ZSTD_CCtx,
-}
-
-impl ZstdCppCompressor {
-    
-    pub fn from_zsdic(zsdic: Arc<ZsDic>, comp_level: i32) -> ZstdCppCompressor {
-        // let zsdic = &zstd.zsdic;
-        // let comp_level = zstd.compressor.comp_level;
-        let zs_cpp = unsafe {
-            ZSTD_createCDict(
-                zsdic.zs_data.as_ptr() as *const std::ffi::c_void,
-                zsdic.zs_data.len(),
-                comp_level,
-            )
-        };
-        let bcett_cpp = unsafe {
-            ZSTD_createCDict(
-                zsdic.bcett_data.as_ptr() as *const std::ffi::c_void,
-                zsdic.bcett_data.len(),
-                comp_level,
-            )
-        };
-        let packzs_cpp = unsafe {
-            ZSTD_createCDict(
-                zsdic.packzs_data.as_ptr() as *const std::ffi::c_void,
-                zsdic.packzs_data.len(),
-                comp_level,
-            )
-        };
-        let empty_cpp = unsafe {
-            ZSTD_createCDict(
-                zsdic.empty_data.as_ptr() as *const std::ffi::c_void,
-                zsdic.empty_data.len(),
-                comp_level,
-            )
-        };
-        let cctx = unsafe { ZSTD_createCCtx() };
-        ZstdCppCompressor {
-            zs_cpp: zs_cpp,
-            bcett_cpp: bcett_cpp,
-            packzs_cpp: packzs_cpp,
-            empty_cpp: empty_cpp,
-            cctx: cctx,
-        }
-    }
-    pub fn compress(&self, data: &[u8], cdict: *mut ZSTD_CDict_s) -> io::Result<Vec<u8>> {
-        // Calculate the maximum compressed size and allocate the buffer
-        let dst_capacity = unsafe { ZSTD_compressBound(data.len()) as usize };
-        let mut buffer: Vec<u8> = vec![0; dst_capacity]; // Allocate space
-        
-        // Perform the compression
-        let res_size = unsafe {
-            ZSTD_compress_usingCDict(
-                self.cctx,
-                buffer.as_mut_ptr() as *mut core::ffi::c_void,
-                dst_capacity,
-                data.as_ptr() as *const core::ffi::c_void,
-                data.len(),
-                cdict,
-            )
-        };
-    
-        // Check for errors
-        if unsafe { ZSTD_isError(res_size) } != 0 {
-            // Handle error (for simplicity, returning a generic I/O error here)
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "ZSTD compression failed"));
-        }
-    
-        // Truncate the buffer to the actual size of the compressed data
-        buffer.truncate(res_size as usize);
-    
-        Ok(buffer)
-    }
-
-    pub fn compress_zs(&self, data: &[u8]) -> io::Result<Vec<u8>> {
-        ZstdCppCompressor::compress(&self, &data, self.zs_cpp)
-    }
-
-    pub fn compress_pack(&self, data: &[u8]) -> io::Result<Vec<u8>> {
-        self.compress( &data, self.packzs_cpp)
-    }
-
-    pub fn compress_bcett(&self, data: &[u8]) -> io::Result<Vec<u8>> {
-        ZstdCppCompressor::compress(&self, &data, self.bcett_cpp)
-    }
+// Dictionaries from Pack/ZsDic.pack.zs, Empty means plain zstd without dictionary
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum ZsDicType {
+    Zs,
+    Pack,
+    Bcett,
+    Empty,
+}
 
-    pub fn compress_empty(&self, data: &[u8]) -> io::Result<Vec<u8>> {
-        ZstdCppCompressor::compress(&self, &data, self.empty_cpp)
+impl ZsDicType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZsDicType::Zs => "zs",
+            ZsDicType::Pack => "pack",
+            ZsDicType::Bcett => "bcett",
+            ZsDicType::Empty => "empty",
+        }
     }
 }
 
@@ -147,19 +70,33 @@ pub struct TotkZstd<'a> {
     pub decompressor: Option<ZstdDecompressor<'a>>,
     pub compressor: Option<ZstdCompressor<'a>>,
     pub zsdic: Option<Arc<ZsDic>>,
-    pub cpp_compressor: Option<ZstdCppCompressor>,
-    //dll
-    pub dll_manager: DllManager
+    //dictionary and level for every .zs save, from config
+    pub compression: CompressionPolicy,
+    //native helpers, loaded on first use
+    pub plugins: PluginRegistry,
+    //prompts (dialogs in the app, NonInteractive in cli)
+    pub ui: Arc<dyn UserInteraction>,
+}
+
+// Shared by the app state, background tasks and batch workers, must stay thread safe
+#[allow(dead_code)]
+fn assert_totk_zstd_send_sync() {
+    fn check<T: Send + Sync>() {}
+    check::<TotkZstd<'static>>();
 }
 
 impl<'a> TotkZstd<'_> {
-    pub fn new(totk_config: Arc<TotkConfig>, comp_level: i32) -> io::Result<TotkZstd<'a>> {
+    pub fn new(totk_config: Arc<TotkConfig>, ui: Arc<dyn UserInteraction>) -> io::Result<TotkZstd<'a>> {
+        let compression = totk_config.compression_policy().unwrap_or_else(|e| {
+            eprintln!("[-] Invalid compression settings, using defaults: {}", e);
+            CompressionPolicy::default()
+        });
+        let comp_level = compression.default_level;
         let mut zsdic: Option<Arc<ZsDic>> = None;
         // let zsdic: Arc<ZsDic> = Arc::new(ZsDic::new(totk_config.clone())?);
         let mut decompressor: Option<ZstdDecompressor<'_>> = None;
         let mut compressor: Option<ZstdCompressor<'_>> = None;
-        let mut cpp_compressor: Option<ZstdCppCompressor> = None;
-        if totk_config.is_valid() {
+        if totk_config.has_zsdic() {
             if let Ok(_zsdic) = ZsDic::new(totk_config.clone()) {
                 let arc_zsdic = Arc::new(_zsdic);
                 zsdic = Some(arc_zsdic.clone());
@@ -170,30 +107,39 @@ impl<'a> TotkZstd<'_> {
             if let Ok(_compressor) = ZstdCompressor::new(totk_config.clone(), arc_zsdic.clone(), comp_level) {
                 compressor = Some(_compressor);
             }
-            if compressor.is_some() {
-                cpp_compressor = Some(ZstdCppCompressor::from_zsdic(arc_zsdic.clone(), comp_level));
-            }
         }
         }
         // let compressor: ZstdCompressor =
         //     ZstdCompressor::new(totk_config.clone(), zsdic.clone(), comp_level)?;
 
+        let plugins = PluginRegistry::new(&totk_config);
         Ok(TotkZstd {
             totk_config,
             decompressor,
             compressor,
             zsdic: zsdic,
-            cpp_compressor: cpp_compressor,
-            dll_manager: DllManager::default()
+            compression,
+            plugins,
+            ui,
         })
     }
 
+    // Romfs dump and dictionaries, needed for comparing with vanilla files
     pub fn is_valid(&self) -> bool {
         self.totk_config.is_valid() && self.zsdic.is_some()
     }
 
+    // Dictionaries loaded, from the romfs or a standalone ZsDic
+    pub fn has_zstd(&self) -> bool {
+        self.zsdic.is_some() && self.compressor.is_some()
+    }
+
     fn throw_zstd_unavailable() -> io::Error {
-        io::Error::new(io::ErrorKind::InvalidInput, "No romfs path found, zstd unavailable")
+        io::Error::new(io::ErrorKind::InvalidInput, "No romfs or ZsDic path found, zstd unavailable")
+    }
+
+    fn throw_romfs_unavailable() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, "No romfs path found, vanilla files unavailable")
     }
 
     pub fn compress_zs(&self, data: &Vec<u8>) -> io::Result<Vec<u8>> {
@@ -224,6 +170,58 @@ impl<'a> TotkZstd<'_> {
         Err(Self::throw_zstd_unavailable())
     }
 
+    //Dictionary and level from the compression policy for given file path (with or without .zs)
+    pub fn compress_by_path<P: AsRef<Path>>(&self, data: &Vec<u8>, path: P) -> io::Result<Vec<u8>> {
+        self.compress_file(data, path, TotkFileType::Other, None)
+    }
+
+    //Every .zs save goes through here. `detected` is the dictionary found when the file was opened
+    pub fn compress_file<P: AsRef<Path>>(&self, data: &Vec<u8>, path: P, file_type: TotkFileType, detected: Option<ZsDicType>) -> io::Result<Vec<u8>> {
+        let choice = self.compression.resolve(&path.as_ref().to_string_lossy(), file_type, detected);
+        self.compress_with_level(data, choice.dict, choice.level)
+    }
+
+    pub fn compress_with_dict(&self, data: &Vec<u8>, dict: ZsDicType) -> io::Result<Vec<u8>> {
+        self.compress_with_level(data, dict, self.compression.default_level)
+    }
+
+    pub fn compress_with_level(&self, data: &Vec<u8>, dict: ZsDicType, level: i32) -> io::Result<Vec<u8>> {
+        if let Some(compressor) = &self.compressor {
+            return compressor.compress_with(data, dict, level);
+        }
+        Err(Self::throw_zstd_unavailable())
+    }
+
+    //Dictionary from the zstd frame header: Empty if the frame has no dictionary id (any dictionary
+    //would decompress it, so it can't be told apart), None if it's not zstd or the id is unknown
+    pub fn detect_dict(&self, data: &[u8]) -> Option<ZsDicType> {
+        let id = get_frame_dict_id(data)?;
+        if id == 0 {
+            return Some(ZsDicType::Empty);
+        }
+        self.zsdic.as_ref()?.get_dict_type(id)
+    }
+
+    //Zstd data is decompressed, anything else returned as is. The dictionary is the one that actually
+    //decompressed it (not only the frame header), so the file is saved back the same way
+    pub fn decompress_if_zstd(&self, data: Vec<u8>) -> io::Result<(Vec<u8>, Option<ZsDicType>)> {
+        if !is_zstd(&data) {
+            return Ok((data, None));
+        }
+        let (dec_data, dict) = self.decompress_detect(&data)?;
+        Ok((dec_data, Some(dict)))
+    }
+
+    //Same, for comparing and searching contents: data no dictionary opens is returned as is
+    pub fn plain_data(&self, data: Vec<u8>) -> Vec<u8> {
+        if is_zstd(&data) {
+            if let Ok(dec_data) = self.try_decompress(&data) {
+                return dec_data;
+            }
+        }
+        data
+    }
+
     pub fn decompress(&self, data: &Vec<u8>, dictt: &Arc<DecoderDictionary>) -> io::Result<Vec<u8>> {
         if let Some(decompressor) = &self.decompressor {
             return decompressor.decompress(data, dictt);
@@ -258,43 +256,52 @@ impl<'a> TotkZstd<'_> {
         Err(Self::throw_zstd_unavailable())
     }
 
-    pub fn try_decompress(&self, data: &Vec<u8>) -> Result<Vec<u8>, io::Error> {
-        // println!("Trying to decompress...");
+    pub fn decompress_with_dict(&self, data: &Vec<u8>, dict: ZsDicType) -> io::Result<Vec<u8>> {
         if let Some(decompressor) = &self.decompressor {
-            let mut dicts: HashMap<String, Arc<DecoderDictionary>> = Default::default();
-            dicts.insert("zs".to_string(), decompressor.zs.clone());
-            dicts.insert("packzs".to_string(), decompressor.packzs.clone());
-            dicts.insert("empty".to_string(), decompressor.empty.clone());
-            dicts.insert("bcett".to_string(), decompressor.bcett.clone());
-    
-            for (name, dictt) in dicts.iter() {
-                if let Ok(dec_data) = self.decompress(&data, &dictt) {
-                    // println!("Finally decompressed! Its {} dictionary", name);
-                    return Ok(dec_data);
-                }
-            }
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Unable to decompress with any dictionary!",
-            ));
+            return decompressor.decompress(data, decompressor.get(dict));
         }
         Err(Self::throw_zstd_unavailable())
+    }
 
-        
+    pub fn try_decompress(&self, data: &Vec<u8>) -> Result<Vec<u8>, io::Error> {
+        self.decompress_detect(data).map(|(dec_data, _)| dec_data)
+    }
+
+    //Decompress with the dictionary named in the frame header. Frames without dictionary id are plain
+    //zstd (Empty), the dictionaries are only tried when that fails
+    pub fn decompress_detect(&self, data: &Vec<u8>) -> io::Result<(Vec<u8>, ZsDicType)> {
+        let detected = self.detect_dict(data);
+        if let Some(dict) = detected {
+            match self.decompress_with_dict(data, dict) {
+                Ok(dec_data) => return Ok((dec_data, dict)),
+                Err(e) if dict != ZsDicType::Empty => return Err(e),
+                Err(_) => {}
+            }
+        }
+        for dict in [ZsDicType::Zs, ZsDicType::Bcett, ZsDicType::Pack] {
+            if let Ok(dec_data) = self.decompress_with_dict(data, dict) {
+                return Ok((dec_data, dict));
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Unable to decompress with any dictionary!",
+        ))
     }
+
     pub fn find_vanila_internal_file_path_in_romfs<P: AsRef<Path>>(&self, internal_path: P) -> io::Result<String> {
-        //parse json
         if !self.is_valid() {
-            return Err(Self::throw_zstd_unavailable());
+            return Err(Self::throw_romfs_unavailable());
         }
-        let json_zlibdata = fs::read("bin/totk_internal_filepaths.bin")?;
-        let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
-        let mut json_str = String::new();
-        decoder.read_to_string(&mut json_str)?;
-        let res: HashMap<String, String> = serde_json::from_str(&json_str)?;
+        let res = get_internal_filepaths_data(&self.totk_config)?;
+        self.find_vanila_internal_file_path_with(&res, internal_path)
+    }
+
+    //Same as above with the internal paths data already parsed, for lookups of many files
+    pub fn find_vanila_internal_file_path_with<P: AsRef<Path>>(&self, internal_filepaths: &HashMap<String, String>, internal_path: P) -> io::Result<String> {
         //find the sarc file
         let int_path_str = internal_path.as_ref().to_string_lossy().to_string();
-        let sarc_localpath = res.get(&int_path_str).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "File not found"))?;
+        let sarc_localpath = internal_filepaths.get(&int_path_str).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "File not found"))?;
         let sarc_filepath = PathBuf::from(&self.totk_config.romfs).join(sarc_localpath);
         Ok(sarc_filepath.to_string_lossy().to_string())
     }
@@ -311,7 +318,7 @@ impl<'a> TotkZstd<'_> {
     }
     pub fn find_vanila_internal_file_data_in_romfs<P: AsRef<Path>>(&self, internal_path: P, zstd: Arc<TotkZstd>) -> io::Result<String> {
         if !zstd.is_valid() {
-            return Err(Self::throw_zstd_unavailable());
+            return Err(Self::throw_romfs_unavailable());
         }
         //parse json
         // let json_zlibdata = fs::read("bin/totk_internal_filepaths.bin")?;
@@ -341,25 +348,31 @@ pub struct ZsDic {
     pub bcett_data: Vec<u8>,
     pub packzs_data: Vec<u8>,
     pub empty_data: Vec<u8>,
+    //dictionary ids, as written in the frame headers of compressed files
+    pub zs_id: u32,
+    pub bcett_id: u32,
+    pub packzs_id: u32,
 }
 
 impl ZsDic {
     pub fn new(totk_config: Arc<TotkConfig>) -> io::Result<ZsDic> {
-        let sarc = ZsDic::get_zsdic_sarc(&totk_config)?;
         let empty_data: Vec<u8> = Vec::new();
         let mut zs_data: Vec<u8> = Vec::new();
         let mut bcett_data: Vec<u8> = Vec::new();
         let mut packzs_data: Vec<u8> = Vec::new();
 
-        for file in sarc.files() {
-            match file.name.unwrap_or_default() {
-                "zs.zsdic" => zs_data = file.data().to_vec(),
-                "bcett.byml.zsdic" => bcett_data = file.data().to_vec(),
-                "pack.zsdic" => packzs_data = file.data().to_vec(),
+        for (name, data) in ZsDic::get_zsdic_files(&totk_config)? {
+            match name.as_str() {
+                "zs.zsdic" => zs_data = data,
+                "bcett.byml.zsdic" => bcett_data = data,
+                "pack.zsdic" => packzs_data = data,
                 _ => (), // pass for other files
             }
         }
         Ok(ZsDic {
+            zs_id: get_dict_id(&zs_data),
+            bcett_id: get_dict_id(&bcett_data),
+            packzs_id: get_dict_id(&packzs_data),
             zs_data: zs_data,
             bcett_data: bcett_data,
             packzs_data: packzs_data,
@@ -367,9 +380,36 @@ impl ZsDic {
         })
     }
 
-    fn get_zsdic_sarc(totk_config: &TotkConfig) -> io::Result<Sarc> {
-        let mut zsdic = PathBuf::from(&totk_config.romfs);
-        zsdic.push("Pack/ZsDic.pack.zs");
+    pub fn get_dict_type(&self, id: u32) -> Option<ZsDicType> {
+        match id {
+            0 => None,
+            _ if id == self.zs_id => Some(ZsDicType::Zs),
+            _ if id == self.packzs_id => Some(ZsDicType::Pack),
+            _ if id == self.bcett_id => Some(ZsDicType::Bcett),
+            _ => None,
+        }
+    }
+
+    // (name, data) of the dictionaries, from ZsDic.pack.zs or from a folder of loose .zsdic files
+    fn get_zsdic_files(totk_config: &TotkConfig) -> io::Result<Vec<(String, Vec<u8>)>> {
+        let zsdic = totk_config.get_zsdic_path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Zsdic file not found: no romfs or ZsDic path configured")
+        })?;
+        if zsdic.is_dir() {
+            let mut res = Vec::new();
+            for name in ZSDIC_FILES {
+                res.push((name.to_string(), fs::read(zsdic.join(name))?));
+            }
+            return Ok(res);
+        }
+        let sarc = ZsDic::get_zsdic_sarc(&zsdic)?;
+        Ok(sarc
+            .files()
+            .map(|file| (file.name.unwrap_or_default().to_string(), file.data().to_vec()))
+            .collect())
+    }
+
+    fn get_zsdic_sarc(zsdic: &Path) -> io::Result<Sarc> {
         if !zsdic.exists() {
             return Err(io::Error::new(io::ErrorKind::NotFound, format!("Zsdic file not found: {:?}", &zsdic)));
         }
@@ -424,6 +464,19 @@ impl<'a> ZstdDecompressor<'_> {
         Err(io::Error::new(io::ErrorKind::Other, err))
     }
 
+    pub fn get(&self, dict: ZsDicType) -> &Arc<DecoderDictionary<'_>> {
+        match dict {
+            ZsDicType::Zs => &self.zs,
+            ZsDicType::Pack => &self.packzs,
+            ZsDicType::Bcett => &self.bcett,
+            ZsDicType::Empty => &self.empty,
+        }
+    }
+
+    pub fn decompress_with(&self, data: &[u8], dict: ZsDicType) -> Result<Vec<u8>, io::Error> {
+        ZstdDecompressor::decompress(&self, &data, self.get(dict))
+    }
+
     pub fn decompress_zs(&self, data: &[u8]) -> Result<Vec<u8>, io::Error> {
         ZstdDecompressor::decompress(&self, &data, &self.zs)
     }
@@ -454,6 +507,11 @@ pub struct ZstdCompressor<'a> {
     // pub packzs_cpp: *mut ZSTD_CDict_s,
     // pub empty_cpp: *mut ZSTD_CDict_s,
     pub comp_level: i32,
+    zsdic: Arc<ZsDic>,
+    //dictionaries prepared for levels other than comp_level, built on first use
+    other_levels: Mutex<HashMap<(ZsDicType, i32), Arc<EncoderDictionary<'static>>>>,
+    //compression contexts reused between calls, one per thread compressing at the same time (batch workers)
+    cctx_pool: Mutex<Vec<CCtx<'static>>>,
 }
 
 #[allow(dead_code)]
@@ -479,6 +537,9 @@ impl<'a> ZstdCompressor<'_> {
             bcett: bcett,
             empty: empty,
             comp_level: comp_level,
+            zsdic: zsdic,
+            other_levels: Mutex::new(HashMap::new()),
+            cctx_pool: Mutex::new(Vec::new()),
             // zs_cpp: zs_cpp,
             // bcett_cpp: bcett_cpp,
             // packzs_cpp: packzs_cpp,
@@ -487,11 +548,52 @@ impl<'a> ZstdCompressor<'_> {
     }
 
     fn compress(&self, data: &[u8], cdict: &EncoderDictionary) -> io::Result<Vec<u8>> {
-        let mut buffer: Vec<u8> = Vec::new();
-        let mut encoder = Encoder::with_prepared_dictionary(&mut buffer, cdict)?;
-        encoder.write_all(data)?;
-        let compressed_data = encoder.finish()?;
-        Ok(compressed_data.to_vec())
+        //a new context is only made when all pooled ones are busy
+        let mut cctx = self
+            .cctx_pool
+            .lock()
+            .ok()
+            .and_then(|mut pool| pool.pop())
+            .unwrap_or_else(CCtx::create);
+        let mut buffer: Vec<u8> = Vec::with_capacity(zstd_safe::compress_bound(data.len()));
+        let res = cctx.compress_using_cdict(&mut buffer, data, cdict.as_cdict());
+        if let Ok(mut pool) = self.cctx_pool.lock() {
+            pool.push(cctx);
+        }
+        res.map(|_| buffer).map_err(|code| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("ZSTD compression failed: {}", zstd_safe::get_error_name(code)),
+            )
+        })
+    }
+
+    pub fn compress_with(&self, data: &[u8], dict: ZsDicType, level: i32) -> io::Result<Vec<u8>> {
+        if level == self.comp_level {
+            let cdict = match dict {
+                ZsDicType::Zs => &self.zs,
+                ZsDicType::Pack => &self.packzs,
+                ZsDicType::Bcett => &self.bcett,
+                ZsDicType::Empty => &self.empty,
+            };
+            return self.compress(data, cdict);
+        }
+        let cdict = {
+            let mut other_levels = self.other_levels.lock().map_err(|_| io::Error::new(io::ErrorKind::Other, "Compressor poisoned"))?;
+            other_levels
+                .entry((dict, level))
+                .or_insert_with(|| {
+                    let dict_data = match dict {
+                        ZsDicType::Zs => &self.zsdic.zs_data,
+                        ZsDicType::Pack => &self.zsdic.packzs_data,
+                        ZsDicType::Bcett => &self.zsdic.bcett_data,
+                        ZsDicType::Empty => &self.zsdic.empty_data,
+                    };
+                    Arc::new(EncoderDictionary::copy(dict_data, level))
+                })
+                .clone()
+        };
+        self.compress(data, &cdict)
     }
 
     pub fn compress_zs(&self, data: &[u8]) -> io::Result<Vec<u8>> {
@@ -511,11 +613,7 @@ impl<'a> ZstdCompressor<'_> {
     }
 
     pub fn find_vanila_file_in_romfs<P: AsRef<Path>>(&self, path: P) -> io::Result<String> {
-        let json_zlibdata = fs::read("bin/totk_filename_to_localpath.bin")?;
-        let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
-        let mut json_str = String::new();
-        decoder.read_to_string(&mut json_str)?;
-        let res: HashMap<String, String> = serde_json::from_str(&json_str)?;
+        let res = self.totk_config.get_filename_to_localpath_data()?;
         let filename = path.as_ref().file_name().unwrap_or_default().to_str().unwrap_or_default();
         let file_in_romfs_path = res.get(filename).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "File not found"))?;
         let result = PathBuf::from(&self.totk_config.romfs).join(file_in_romfs_path);
@@ -525,6 +623,43 @@ impl<'a> ZstdCompressor<'_> {
    
 }
 
+// Internal path -> romfs path of the sarc holding it
+pub fn get_internal_filepaths_data(config: &TotkConfig) -> io::Result<Arc<HashMap<String, String>>> {
+    config.index.internal_filepaths(config)
+}
+
+#[inline]
+pub fn is_zstd(data: &[u8]) -> bool {
+    data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD])
+}
+
+// Dictionaries inside ZsDic.pack.zs, also the expected names in a standalone ZsDic folder
+pub const ZSDIC_FILES: [&str; 3] = ["zs.zsdic", "pack.zsdic", "bcett.byml.zsdic"];
+const ZSTD_DICT_MAGIC: [u8; 4] = [0x37, 0xA4, 0x30, 0xEC];
+
+//Dictionary id field of the frame header (0 when absent), None if data is not a zstd frame
+pub fn get_frame_dict_id(data: &[u8]) -> Option<u32> {
+    if !is_zstd(data) {
+        return None;
+    }
+    let descriptor = *data.get(4)?;
+    let single_segment = descriptor & 0x20 != 0;
+    let start = if single_segment { 5 } else { 6 };
+    let size = [0, 1, 2, 4][(descriptor & 0x03) as usize];
+    let field = data.get(start..start + size)?;
+    let mut id = [0u8; 4];
+    id[..size].copy_from_slice(field);
+    Some(u32::from_le_bytes(id))
+}
+
+//Id stored in a zstd dictionary, raw content dictionaries have none (0)
+pub fn get_dict_id(dict: &[u8]) -> u32 {
+    if dict.len() >= 8 && dict.starts_with(&ZSTD_DICT_MAGIC) {
+        return u32::from_le_bytes([dict[4], dict[5], dict[6], dict[7]]);
+    }
+    0
+}
+
 #[inline]
 pub fn is_byml(data: &[u8]) -> bool {
     data.starts_with(b"BY") || data.starts_with(b"YB")
@@ -618,6 +753,60 @@ pub fn is_evfl_path<P: AsRef<Path>>(path: P) -> bool {
     path.as_ref().to_string_lossy().to_ascii_lowercase().ends_with(".bfevfl.zs")
         || path.as_ref().to_string_lossy().to_ascii_lowercase().ends_with(".bfevfl")
 }
+// Type names used by the cli and the search filter
+pub fn file_type_from_str(s: &str) -> Option<TotkFileType> {
+    match s.to_lowercase().as_str() {
+        "byml" => Some(TotkFileType::Byml),
+        "bcett" => Some(TotkFileType::Bcett),
+        "aamp" => Some(TotkFileType::Aamp),
+        "msbt" => Some(TotkFileType::Msbt),
+        "ainb" => Some(TotkFileType::AINB),
+        "asb" => Some(TotkFileType::ASB),
+        "tag" => Some(TotkFileType::TagProduct),
+        "evfl" => Some(TotkFileType::Evfl),
+        "xlink" => Some(TotkFileType::Xlink),
+        "text" => Some(TotkFileType::Text),
+        _ => None,
+    }
+}
+
+// Guess from the file name, for text converted back to binary and for filtering before parsing
+pub fn file_type_from_path<P: AsRef<Path>>(path: P) -> Option<TotkFileType> {
+    let path = path.as_ref();
+    if is_tagproduct_path(path) {
+        return Some(TotkFileType::TagProduct);
+    }
+    if is_xlink_path(path) {
+        return Some(TotkFileType::Xlink);
+    }
+    if is_banc_path(path) {
+        return Some(TotkFileType::Bcett);
+    }
+    if is_byml_path(path) {
+        return Some(TotkFileType::Byml);
+    }
+    if is_msbt_path(path) {
+        return Some(TotkFileType::Msbt);
+    }
+    if is_evfl_path(path) {
+        return Some(TotkFileType::Evfl);
+    }
+    let mut p = path.to_string_lossy().to_lowercase();
+    if p.ends_with(".zs") {
+        p = p[..p.len() - 3].to_string();
+    }
+    if is_ainb_path(&p) {
+        return Some(TotkFileType::AINB);
+    }
+    if is_asb_path(&p) {
+        return Some(TotkFileType::ASB);
+    }
+    if p.rsplit('.').next().unwrap_or_default().starts_with('b') {
+        return Some(TotkFileType::Aamp); //bxml, bgparamlist and the like
+    }
+    None
+}
+
 #[inline]
 pub fn is_sarc_root_path(path: &str) -> bool {
     path.is_empty() || path.replace("\\", "/") == "/" || path == "."
@@ -666,45 +855,3 @@ pub fn get_executable_dir() -> String {
     return String::new();
 }
 
-// DLLS
-
-pub struct CppDll {
-    pub path: String,
-    pub lib: Library
-}
-
-impl CppDll {
-    pub fn get_function<T>(&self, func_name: &str) -> io::Result<Symbol<T>> {
-        unsafe {
-            let lib = &self.lib;
-            let mut func_name_bytes = func_name.as_bytes().to_vec();
-            func_name_bytes.push(0); // Null-terminate the string
-            match lib.get(func_name_bytes.as_slice()) {
-                Ok(func) => Ok(func),
-                Err(_) => Err(io::Error::new(io::ErrorKind::NotFound, "Function not found")),
-            }
-        }
-    }
-}
-
-
-pub struct DllManager {
-    pub xlink_dll: CppDll,
-}
-
-impl Default for DllManager {
-    fn default() -> Self {
-        // let mut xlink_dll = CppDll { path: String::new() };
-        let exe_dir = get_executable_dir();
-        let dll_path = format!("{}/bin/dlls/xlink_tool.dll", exe_dir);
-        let lib;
-        unsafe {
-            lib = Library::new(&dll_path).expect("Failed to load DLL");
-        }
-        let xlink_dll = CppDll { path: dll_path, lib };
-        DllManager {
-            xlink_dll: xlink_dll,
-        }
-    }
-}
-
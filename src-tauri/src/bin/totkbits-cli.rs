@@ -0,0 +1,489 @@
+#![allow(non_snake_case, non_camel_case_types)]
+// Headless front-end for build scripts, same core as the Tauri app but no window
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::{env, fs, io, process};
+
+use roead::sarc::SarcWriter;
+use totkbits_core::Batch::{batch_zstd_folder, BatchMode};
+use totkbits_core::DiffReport::PackDiffReport;
+use totkbits_core::FolderSearch::{search_folder, SearchOptions};
+use totkbits_core::file_format::BinTextFile::OpenedFile;
+use totkbits_core::file_format::Pack::{PackComparer, PackFile};
+use totkbits_core::file_format::Rstb::{parse_rstb_hash, Restbl};
+use totkbits_core::file_format::Wrapper::{check_external_tools, tools_report};
+use totkbits_core::Interaction::NonInteractive;
+use totkbits_core::Minimize::minimize_folder;
+use totkbits_core::Open_and_Save::{file_from_disk_to_senddata, get_binary_by_filetype};
+use totkbits_core::RomfsIndexer::build_romfs_index;
+use totkbits_core::RstbCalc::regenerate_rstb;
+use totkbits_core::RstbPatch::{rstb_game_version, RstbPatch};
+use totkbits_core::Settings::{list_files_recursively, makedirs, write_string_to_file, Pathlib};
+use totkbits_core::TotkConfig::TotkConfig;
+use totkbits_core::Zstd::{
+    file_type_from_path, file_type_from_str, get_executable_dir, is_zstd, TotkFileType, TotkZstd,
+};
+
+const USAGE: &str = "Usage: totkbits-cli <command> [args]
+
+Commands:
+  decompress <input> [output]               decompress .zs file (dictionary is detected)
+  compress <input> [output]                 compress file, dictionary and level from the compression rules in config
+  decompress-dir <src_dir> [dest_dir]       decompress all .zs files in folder, in parallel (in place by default)
+  compress-dir <src_dir> [dest_dir]         compress all files in folder, in parallel (in place by default)
+  to-text <input> [output]                  convert binary file to yaml/json text
+  from-text <input> <output> [--type T] [--be]
+                                            convert text back to binary, type is guessed from output path
+                                            (T: byml, bcett, aamp, msbt, ainb, asb, tag, evfl, xlink, text)
+  sarc list <pack>                          list files in sarc
+  sarc extract <pack> <dest_dir> [folder]   extract all files (or single sarc folder)
+  sarc pack <source_dir> <output> [--be]    create sarc from folder, compressed if output ends with .zs
+  sarc minimize <pack> [output] [--dry-run] remove entries identical to vanilla (in place by default)
+  sarc diff <pack> [output]                 added/modified/removed entries vs vanilla, markdown or .json
+  minimize-dir <mod_dir> [--dry-run]        delete files identical to the romfs dump
+  search <folder> <query> [--type T,T]      text search in all files and sarc entries, romfs dump if folder is `romfs`
+                                            (T as in from-text, not case sensitive)
+  index-romfs                               rebuild the vanilla databases from the romfs dump, for its game version
+  rstb get <rstb> <entry>                   print entry size, entry is a path or a raw hash (0x1234abcd)
+  rstb set <rstb> <entry> <size> [output]   set entry size and save (in place by default)
+  rstb regenerate <mod_romfs> [--dry-run]   estimate sizes of added/modified files into the mod's RSTB
+  rstb diff <rstb> <patch>                  export entries differing from vanilla of the same version, .yml or .json
+  rstb patch <rstb> <patch> [output]        apply patch from any game version, lists conflicts (in place by default)
+  check-tools                               check python and .NET converters (paths from config)
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args[0] == "-h" || args[0] == "--help" {
+        print!("{}", USAGE);
+        return;
+    }
+    if let Err(e) = run(args) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> io::Result<()> {
+    let (mut positional, flags) = split_flags(&args[1..]);
+    //paths are relative to the caller, but bin/ resources are relative to the executable
+    let cwd = env::current_dir()?;
+    let sub = positional.get(0).cloned().unwrap_or_default();
+    for i in path_arg_indices(&args[0], &sub) {
+        if let Some(arg) = positional.get_mut(*i) {
+            *arg = cwd.join(&*arg).to_string_lossy().to_string().replace("\\", "/");
+        }
+    }
+    let exe_cwd = get_executable_dir();
+    if !exe_cwd.is_empty() {
+        env::set_current_dir(&exe_cwd)?;
+    }
+    let zstd = init_zstd()?;
+
+    match args[0].as_str() {
+        "decompress" => decompress(&positional, zstd),
+        "compress" => compress(&positional, zstd),
+        "decompress-dir" => batch_dir(&positional, BatchMode::Decompress, zstd),
+        "compress-dir" => batch_dir(&positional, BatchMode::Compress, zstd),
+        "to-text" => to_text(&positional, zstd),
+        "from-text" => from_text(&positional, &flags, zstd),
+        "sarc" => sarc(&sub, positional.get(1..).unwrap_or_default(), &flags, zstd),
+        "rstb" => rstb(&sub, positional.get(1..).unwrap_or_default(), &flags, zstd),
+        "minimize-dir" => minimize_dir(&positional, &flags, zstd),
+        "index-romfs" => index_romfs(zstd),
+        "search" => search(&positional, &flags, zstd),
+        "check-tools" => check_tools(zstd),
+        cmd => Err(invalid_input(format!("Unknown command: {}\n\n{}", cmd, USAGE))),
+    }
+}
+
+//Positional arguments (counting the subcommand) that are filesystem paths
+fn path_arg_indices(cmd: &str, sub: &str) -> &'static [usize] {
+    match (cmd, sub) {
+        ("decompress", _) | ("compress", _) | ("to-text", _) | ("from-text", _) => &[0, 1],
+        ("decompress-dir", _) | ("compress-dir", _) => &[0, 1],
+        ("minimize-dir", _) => &[0],
+        ("search", _) if sub != "romfs" => &[0],
+        ("sarc", "minimize") | ("sarc", "diff") => &[1, 2],
+        ("sarc", "list") => &[1],
+        ("sarc", "extract") | ("sarc", "pack") => &[1, 2],
+        ("rstb", "get") => &[1],
+        ("rstb", "set") => &[1, 4],
+        ("rstb", "regenerate") => &[1],
+        ("rstb", "diff") => &[1, 2],
+        ("rstb", "patch") => &[1, 2, 3],
+        _ => &[],
+    }
+}
+
+fn init_zstd<'a>() -> io::Result<Arc<TotkZstd<'a>>> {
+    //no one to answer prompts here: confirmations are declined, file pickers return nothing
+    let ui = Arc::new(NonInteractive::default());
+    let totk_config = Arc::new(TotkConfig::from_toml(ui.as_ref()).unwrap_or_default());
+    let zstd = TotkZstd::new(totk_config, ui)?;
+    if !zstd.has_zstd() {
+        eprintln!("[-] No valid romfs or ZsDic path in config, zstd unavailable");
+    } else if !zstd.is_valid() {
+        eprintln!("[-] No valid romfs path in config, vanilla comparison unavailable");
+    }
+    Ok(Arc::new(zstd))
+}
+
+//Returns (positional args, flags with their values). Flags are --be and --type <T>
+fn split_flags(args: &[String]) -> (Vec<String>, Vec<(String, String)>) {
+    let mut positional = Vec::new();
+    let mut flags = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "--type" {
+            flags.push((arg.clone(), args.get(i + 1).cloned().unwrap_or_default()));
+            i += 2;
+            continue;
+        }
+        if arg.starts_with("--") {
+            flags.push((arg.clone(), String::new()));
+        } else {
+            positional.push(arg.clone());
+        }
+        i += 1;
+    }
+    (positional, flags)
+}
+
+fn get_flag<'f>(flags: &'f [(String, String)], name: &str) -> Option<&'f str> {
+    flags
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.as_str())
+}
+
+fn invalid_input(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn arg_at(args: &[String], i: usize, name: &str) -> io::Result<String> {
+    args.get(i)
+        .cloned()
+        .ok_or_else(|| invalid_input(format!("Missing argument: <{}>\n\n{}", name, USAGE)))
+}
+
+fn write_bytes(path: &str, data: &[u8]) -> io::Result<()> {
+    makedirs(&PathBuf::from(path))?;
+    fs::write(path, data)
+}
+
+fn decompress(args: &[String], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let input = arg_at(args, 0, "input")?;
+    let output = args.get(1).cloned().unwrap_or_else(|| {
+        if input.to_lowercase().ends_with(".zs") {
+            input[..input.len() - 3].to_string()
+        } else {
+            format!("{}.dec", &input)
+        }
+    });
+    let data = fs::read(&input)?;
+    if !is_zstd(&data) {
+        return Err(invalid_input(format!("Not a zstd file: {}", &input)));
+    }
+    let (dec_data, dict) = zstd.decompress_detect(&data)?;
+    write_bytes(&output, &dec_data)?;
+    println!("Decompressed {} -> {} [{} dictionary]", &input, &output, dict.as_str());
+    Ok(())
+}
+
+fn compress(args: &[String], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let input = arg_at(args, 0, "input")?;
+    let output = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(|| format!("{}.zs", &input));
+    let data = fs::read(&input)?;
+    let comp_data = zstd.compress_by_path(&data, &output)?;
+    write_bytes(&output, &comp_data)?;
+    println!("Compressed {} -> {}", &input, &output);
+    Ok(())
+}
+
+fn batch_dir(args: &[String], mode: BatchMode, zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let src = arg_at(args, 0, "src_dir")?;
+    let dest = args.get(1).cloned().unwrap_or_else(|| src.clone());
+    let report = batch_zstd_folder(&zstd, Path::new(&src), Path::new(&dest), mode, |progress| {
+        eprint!("\r[{}/{}]", progress.done, progress.total);
+    })?;
+    eprintln!();
+    for failure in &report.failures {
+        eprintln!("[-] {}: {}", failure.path, failure.error);
+    }
+    println!("{}", report.summary(mode));
+    if !report.failures.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::Other, format!("{} file(s) failed", report.failures.len())));
+    }
+    Ok(())
+}
+
+fn minimize_dir(args: &[String], flags: &[(String, String)], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let folder = arg_at(args, 0, "mod_dir")?;
+    let report = minimize_folder(&zstd, Path::new(&folder), get_flag(flags, "--dry-run").is_some())?;
+    for entry in &report.entries {
+        println!("{}", entry.path);
+    }
+    for failure in &report.failures {
+        eprintln!("[-] {}: {}", failure.path, failure.error);
+    }
+    println!("{}", report.summary());
+    if !report.failures.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::Other, format!("{} file(s) failed", report.failures.len())));
+    }
+    Ok(())
+}
+
+fn to_text(args: &[String], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let input = arg_at(args, 0, "input")?;
+    let (_, data) = file_from_disk_to_senddata(&input, zstd.clone())
+        .ok_or_else(|| invalid_input(format!("Unable to convert to text: {}", &input)))?;
+    let output = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(|| format!("{}.{}", &input, &data.lang));
+    makedirs(&PathBuf::from(&output))?;
+    write_string_to_file(&output, &data.text)?;
+    println!("Converted {} -> {}", &input, &output);
+    Ok(())
+}
+
+fn from_text(args: &[String], flags: &[(String, String)], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let input = arg_at(args, 0, "input")?;
+    let output = arg_at(args, 1, "output")?;
+    let file_type = match get_flag(flags, "--type") {
+        Some(t) => file_type_from_str(t).ok_or_else(|| invalid_input(format!("Unknown type: {}", t)))?,
+        None => file_type_from_path(&output).ok_or_else(|| {
+            invalid_input(format!("Unable to guess file type from: {}, use --type", &output))
+        })?,
+    };
+    let endian = if get_flag(flags, "--be").is_some() {
+        roead::Endian::Big
+    } else {
+        roead::Endian::Little
+    };
+    let text = fs::read_to_string(&input)?;
+    let mut opened_file = OpenedFile::from_path(output.clone(), file_type);
+    let rawdata = get_binary_by_filetype(file_type, &text, endian, zstd.clone(), &output, &mut opened_file, None)
+        .unwrap_or_default();
+    if rawdata.is_empty() {
+        return Err(invalid_input(format!("Failed to convert {} to [{:?}]", &input, file_type)));
+    }
+    write_bytes(&output, &rawdata)?;
+    println!("Converted {} -> {} [{:?}]", &input, &output, file_type);
+    Ok(())
+}
+
+fn sarc(sub: &str, args: &[String], flags: &[(String, String)], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    match sub {
+        "list" => {
+            let pack = PackFile::new(arg_at(args, 0, "pack")?, zstd.clone())?;
+            let mut names: Vec<String> = pack
+                .sarc
+                .files()
+                .filter_map(|f| f.name.map(|n| n.to_string()))
+                .collect();
+            names.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+            for name in names {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        "extract" => {
+            let pack = PackFile::new(arg_at(args, 0, "pack")?, zstd.clone())?;
+            let dest = PathBuf::from(arg_at(args, 1, "dest_dir")?);
+            let mut prefix = args.get(2).cloned().unwrap_or_default();
+            if !prefix.is_empty() && !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            let mut i = 0;
+            for file in pack.sarc.files() {
+                if let Some(name) = file.name {
+                    if name.starts_with(&prefix) {
+                        let file_path = dest.join(name);
+                        makedirs(&file_path)?;
+                        fs::write(&file_path, file.data)?;
+                        i += 1;
+                    }
+                }
+            }
+            println!("Extracted {} files to {}", i, dest.display());
+            Ok(())
+        }
+        "pack" => {
+            let source = arg_at(args, 0, "source_dir")?.replace("\\", "/");
+            let output = arg_at(args, 1, "output")?;
+            let endian = if get_flag(flags, "--be").is_some() {
+                roead::Endian::Big
+            } else {
+                roead::Endian::Little
+            };
+            let mut pack = PackFile::default(zstd.clone())?;
+            pack.writer = SarcWriter::new(endian);
+            pack.endian = endian;
+            pack.path = Pathlib::new(&output);
+            if output.to_lowercase().ends_with(".sarc.zs") {
+                pack.file_type = TotkFileType::MalsSarc;
+            }
+            let root_len = source.trim_end_matches('/').len();
+            let files = list_files_recursively(&source);
+            for file in files.iter() {
+                let internal_path = file[root_len..].trim_start_matches('/').to_string();
+                pack.writer.add_file(&internal_path, fs::read(file)?);
+            }
+            pack.save(output.clone())?;
+            println!("Packed {} files to {}", files.len(), &output);
+            Ok(())
+        }
+        "minimize" => {
+            let path = arg_at(args, 0, "pack")?;
+            let output = args.get(1).cloned().unwrap_or_else(|| path.clone());
+            let pack = PackFile::new(&path, zstd.clone())?;
+            let mut pack = PackComparer::from_pack(pack, zstd.clone())
+                .ok_or_else(|| invalid_input(format!("Unable to compare {} with vanilla", &path)))?;
+            let dry_run = get_flag(flags, "--dry-run").is_some();
+            let report = pack.minimize(dry_run);
+            for entry in &report.entries {
+                println!("{}", entry.path);
+            }
+            if !dry_run && !report.entries.is_empty() {
+                if let Some(opened) = &mut pack.opened {
+                    opened.save(output.clone())?;
+                }
+            }
+            println!("{}", report.summary());
+            Ok(())
+        }
+        "diff" => {
+            let path = arg_at(args, 0, "pack")?;
+            let pack = PackComparer::from_pack(PackFile::new(&path, zstd.clone())?, zstd.clone())
+                .ok_or_else(|| invalid_input(format!("Unable to compare {} with vanilla", &path)))?;
+            let report = PackDiffReport::from_pack(&pack, zstd.clone());
+            match args.get(1) {
+                Some(output) => {
+                    let text = if output.to_lowercase().ends_with(".json") {
+                        report.to_json()
+                    } else {
+                        report.to_markdown()
+                    };
+                    makedirs(&PathBuf::from(output))?;
+                    write_string_to_file(output, &text)?;
+                    println!("{} -> {}", report.summary(), output);
+                }
+                None => print!("{}", report.to_markdown()),
+            }
+            Ok(())
+        }
+        _ => Err(invalid_input(format!("Unknown sarc command: {}\n\n{}", sub, USAGE))),
+    }
+}
+
+fn rstb(sub: &str, args: &[String], flags: &[(String, String)], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    if sub == "regenerate" {
+        let folder = arg_at(args, 0, "mod_romfs")?;
+        let report = regenerate_rstb(zstd.clone(), Path::new(&folder), get_flag(flags, "--dry-run").is_some())?;
+        if !report.is_empty() {
+            println!("{}", report.listing(usize::MAX));
+        }
+        println!("{}", report.summary());
+        return Ok(());
+    }
+    let path = arg_at(args, 0, "rstb")?;
+    let mut restbl = Restbl::from_path(&path, zstd.clone())
+        .ok_or_else(|| invalid_input(format!("Unable to open rstb: {}", &path)))?;
+    if sub == "diff" {
+        let output = arg_at(args, 1, "patch")?;
+        let patch = RstbPatch::from_restbl(&restbl)?;
+        patch.save(&output)?;
+        println!("Exported {} RSTB entries (game version {}) to {}", patch.entries.len(), &patch.game_version, &output);
+        return Ok(());
+    }
+    if sub == "patch" {
+        let patch = RstbPatch::from_file(arg_at(args, 1, "patch")?)?;
+        let output = args.get(2).cloned().unwrap_or(path.clone());
+        let target_version = rstb_game_version(&restbl.path.name).unwrap_or("unknown".to_string());
+        let report = patch.apply(&mut restbl.table, &target_version)?;
+        if !report.conflicts.is_empty() {
+            println!("{}", report.conflicts_report(usize::MAX));
+        }
+        restbl.save(&output)?;
+        println!("{}", report.summary());
+        return Ok(());
+    }
+    let entry = arg_at(args, 1, "entry")?;
+    match sub {
+        "get" => {
+            let val = match parse_rstb_hash(&entry) {
+                Some(hash) => restbl.get_hash(hash)?,
+                None => restbl.table.get(entry.clone()),
+            };
+            match val {
+                Some(val) => println!("{}", val),
+                None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("Entry absent in RSTB: {}", &entry))),
+            }
+            Ok(())
+        }
+        "set" => {
+            let val = arg_at(args, 2, "size")?
+                .parse::<u32>()
+                .map_err(|e| invalid_input(format!("Invalid size: {}", e)))?;
+            let output = args.get(3).cloned().unwrap_or(path.clone());
+            match parse_rstb_hash(&entry) {
+                Some(hash) => {
+                    restbl.set_hash(hash, val)?;
+                }
+                None => restbl.set_named(&entry, val)?,
+            }
+            restbl.save(&output)?;
+            println!("Set {} = {} in {}", &entry, val, &output);
+            Ok(())
+        }
+        _ => Err(invalid_input(format!("Unknown rstb command: {}\n\n{}", sub, USAGE))),
+    }
+}
+
+fn index_romfs(zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let report = build_romfs_index(zstd, |progress| {
+        eprint!("\r[{}/{}]", progress.done, progress.total);
+    })?;
+    eprintln!();
+    for failure in &report.failures {
+        eprintln!("[-] {}: {}", failure.path, failure.error);
+    }
+    println!("{}", report.summary());
+    println!("Written to {}", report.output);
+    Ok(())
+}
+
+fn search(args: &[String], flags: &[(String, String)], zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let folder = arg_at(args, 0, "folder")?;
+    let query = arg_at(args, 1, "query")?;
+    let folder = if folder == "romfs" { zstd.totk_config.romfs.clone() } else { folder };
+    let options = SearchOptions::new(&query, get_flag(flags, "--type").unwrap_or_default())?;
+    let cancel = AtomicBool::new(false);
+    let report = search_folder(zstd.clone(), Path::new(&folder), &options, &cancel, |_| {}, |matches| {
+        for m in matches {
+            match m.internal_path.is_empty() {
+                true => println!("{}:{}: {}", m.path, m.line, m.snippet),
+                false => println!("{}>{}:{}: {}", m.path, m.internal_path, m.line, m.snippet),
+            }
+        }
+    })?;
+    eprintln!("{}", report.summary());
+    Ok(())
+}
+
+fn check_tools(zstd: Arc<TotkZstd>) -> io::Result<()> {
+    let checks = check_external_tools(&zstd.totk_config);
+    println!("{}", tools_report(&checks));
+    let failed = checks.iter().filter(|c| c.result.is_err()).count();
+    if failed > 0 {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} external converter(s) unavailable", failed)));
+    }
+    Ok(())
+}
@@ -1,12 +1,12 @@
 #![allow(non_snake_case,non_camel_case_types)]
 use std::{
-    io::{self, Read, Write}, os::windows::process::CommandExt, path::Path, process::{Command, Stdio}, sync::Arc
+    io::{self, Read}, path::Path, process::Command, sync::Arc
 };
 
 
-use crate::{Open_and_Save::SendData, Settings::{Pathlib, NO_WINDOW_FLAG}, Zstd::{is_ainb, TotkFileType, TotkZstd}};
+use crate::{Open_and_Save::SendData, Settings::{NoWindow, Pathlib, NO_WINDOW_FLAG}, TotkConfig::TotkConfig, Zstd::{is_ainb, TotkFileType, TotkZstd}};
 
-use super::BinTextFile::OpenedFile;
+use super::{BinTextFile::OpenedFile, Worker::WorkerPool};
 
 pub struct Ainb_py {
     pub python_exe: String,
@@ -16,11 +16,7 @@ pub struct Ainb_py {
 
 impl Default for Ainb_py {
     fn default() -> Self {
-        Self {
-            python_exe: "bin/winpython/python-3.11.8.amd64/python.exe".to_string(),
-            python_script: "totkbits.py".to_string(),
-            create_no_window: NO_WINDOW_FLAG,
-        }
+        Self::from_config(&TotkConfig::default())
     }
 }
 
@@ -30,12 +26,20 @@ impl Ainb_py {
         Self::default()
     }
 
+    pub fn from_config(totk_config: &TotkConfig) -> Self {
+        Self {
+            python_exe: totk_config.get_python_exe(),
+            python_script: totk_config.get_python_script(),
+            create_no_window: NO_WINDOW_FLAG,
+        }
+    }
+
     pub fn open_ainb<P: AsRef<Path>>(path: P, zstd: Arc<TotkZstd>) -> Option<(OpenedFile, SendData)> {
         let mut opened_file = OpenedFile::default();
         let mut data = SendData::default();
         let path_ref = path.as_ref();
         print!("Is {} a ainb? ", &path_ref.display());
-        match Ainb_py::new().binary_file_to_text(path_ref) {
+        match Ainb_py::from_config(&zstd.totk_config).binary_file_to_text(path_ref) {
             Ok(text) => {
                 println!(" yes!");
                 opened_file.path = Pathlib::new(path_ref);
@@ -81,86 +85,27 @@ impl Ainb_py {
         Ok(data)
     }
 
-    pub fn binary_to_text(&self, data: &Vec<u8>) -> io::Result<String> {
-        // env::set_var("PATH", self.newpath.clone());
-        let mut child = Command::new(&self.python_exe)
-            // .current_dir(&self.current_dir)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg("ainb_binary_to_text")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(data)?;
-            // For binary data, ensure you're handling errors and using `write_all` to guarantee all data is written.
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-        if stdout.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stdout));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
+    pub fn pool(&self) -> Arc<WorkerPool> {
+        WorkerPool::shared(&self.python_exe, &[self.python_script.clone()])
+    }
 
-        if output.status.success() {
-            println!("Script executed successfully.");
-        } else {
-            // eprintln!("Script execution failed.");
-            eprintln!("Script execution failed. {:#?}\n{}", output.status, &stderr);
-            // eprintln!("Data: {:?}", &stdout);
-            return Err(io::Error::new(io::ErrorKind::Other, "Script execution failed."));
-        }
-        Ok(stdout)
+    pub fn binary_to_text(&self, data: &Vec<u8>) -> io::Result<String> {
+        let stdout = self.pool().call("ainb_binary_to_text", data)?;
+        println!("Script executed successfully.");
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
     }
 
     pub fn text_to_binary(&self, text: &str) -> io::Result<Vec<u8>> {
-        let mut child = Command::new(&self.python_exe)
-            // .current_dir(&self.current_dir)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg("ainb_text_to_binary")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(text.as_bytes())?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        if output.stdout.starts_with(b"Error") {
-            return Err(io::Error::new(io::ErrorKind::Other, String::from_utf8_lossy(&output.stdout).into_owned()));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
-
-        if output.status.success() {
-            println!("Script executed successfully.");
-        } else {
-            eprintln!("Script execution failed.");
-            let e = format!("Script execution failed. Unable to convert ainb text to binary. \n{:#?}\n{}", output.status, &stderr);
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                e,
-            ));
-        }
-        Ok(output.stdout)
+        let data = self.pool().call("ainb_text_to_binary", text.as_bytes())?;
+        println!("Script executed successfully.");
+        Ok(data)
     }
     
     pub fn test_winpython(&self) -> io::Result<()> {
         // env::set_var("PATH", self.newpath.clone());
         let output = Command::new(&self.python_exe)
             .arg(&self.python_script)
-            .creation_flags(self.create_no_window)
+            .no_window(self.create_no_window)
             // .arg("-V")
             .output()?;
         if output.status.success() {
@@ -1,16 +1,16 @@
 #![allow(non_snake_case, non_camel_case_types)]
 use crate::file_format::BinTextFile::OpenedFile;
+use crate::file_format::Worker::WorkerPool;
 use crate::Open_and_Save::SendData;
-use crate::Settings::{Pathlib, NO_WINDOW_FLAG};
-use crate::Zstd::{is_asb, is_baev, TotkFileType, TotkZstd};
-use rfd::{FileDialog, MessageDialog};
+use crate::Settings::{NoWindow, Pathlib, NO_WINDOW_FLAG};
+use crate::Interaction::BaevChoice;
+use crate::Zstd::{is_asb, is_baev, TotkFileType, TotkZstd, ZsDicType};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{
     io::{self, Read, Write},
-    os::windows::process::CommandExt,
-    process::{Command, Stdio},
+    process::Command,
 };
 
 pub const ASB_SEPARATOR: &[u8; 15] =  b"%ASB_SEPARATOR%";
@@ -23,6 +23,7 @@ pub struct Asb_py<'a> {
     pub data: Vec<u8>,
     pub baev_data: Vec<u8>,
     pub name: String,
+    pub zs_dict: Option<ZsDicType>, //dictionary the file was compressed with
 }
 
 #[allow(dead_code, unused_variables)]
@@ -30,12 +31,13 @@ impl<'a> Asb_py<'a> {
     pub fn new(zstd: Arc<TotkZstd<'a>>) -> Asb_py<'a> {
         Self {
             zstd: zstd.clone(),
-            python_exe: "bin/winpython/python-3.11.8.amd64/python.exe".to_string(),
-            python_script: "totkbits.py".to_string(),
+            python_exe: zstd.totk_config.get_python_exe(),
+            python_script: zstd.totk_config.get_python_script(),
             create_no_window: NO_WINDOW_FLAG,
             data: Vec::new(),
             baev_data: Vec::new(),
             name: "".to_string(),
+            zs_dict: None,
         }
     }
     pub fn from_binary_file<P: AsRef<Path>>(
@@ -68,10 +70,10 @@ impl<'a> Asb_py<'a> {
     }
 
     pub fn get_baev_data_select(zstd: Arc<TotkZstd<'a>>, name: String) -> io::Result<Vec<u8>> {
-        let baev_path = FileDialog::new()
-        .add_filter("BAEV files", &["baev", "baev.zs"])
-        .set_title("Select a BAEV or BAEV.ZS file")
-        .pick_file().unwrap_or_default().to_string_lossy().to_string();
+        let filters = vec![("BAEV files".to_string(), vec!["baev".to_string(), "baev.zs".to_string()])];
+        let baev_path = zstd.ui
+        .pick_file("Select a BAEV or BAEV.ZS file", &filters)
+        .unwrap_or_default().to_string_lossy().to_string();
         let mut data = Vec::new();
         if baev_path.is_empty(){
             return Ok(data);
@@ -93,19 +95,14 @@ impl<'a> Asb_py<'a> {
             return Ok(b"".to_vec());
         }
 
-        let msg_window = MessageDialog::new()
-        .set_title("Select BAEV file")
-        .set_description("Would you like to select BAEV file manually? Select \"No\" to import data from romfs dump or \"Cancel\" if you want to skip baev data")
-        .set_buttons(rfd::MessageButtons::YesNoCancel)
-        .show();
-    match msg_window {
-        rfd::MessageDialogResult::Yes => {
+    match zstd.ui.choose_baev() {
+        BaevChoice::Select => {
             Self::get_baev_data_select(zstd.clone(), name)
         }
-        rfd::MessageDialogResult::No => {
+        BaevChoice::FromRomfs => {
             Self::get_baev_data_from_romfs(zstd.clone(), name)
         }
-        _ => {
+        BaevChoice::Skip => {
             Ok(b"".to_vec())
             }
         }
@@ -117,10 +114,11 @@ impl<'a> Asb_py<'a> {
         file_path: P,
     ) -> io::Result<Asb_py<'a>> {
         let name = Pathlib::new(file_path.as_ref()).stem;
-        let new_data = if !is_asb(data) {
-            zstd.decompress_zs(data)?
+        let (new_data, zs_dict) = if !is_asb(data) {
+            let (dec_data, dict) = zstd.decompress_detect(data)?;
+            (dec_data, Some(dict))
         } else {
-            data.to_vec()
+            (data.to_vec(), None)
         };
         if !is_asb(&new_data) {
             return Err(io::Error::new(
@@ -142,12 +140,13 @@ impl<'a> Asb_py<'a> {
 
         Ok(Self {
             zstd: zstd.clone(),
-            python_exe: "bin/winpython/python-3.11.8.amd64/python.exe".to_string(),
-            python_script: "totkbits.py".to_string(),
+            python_exe: zstd.totk_config.get_python_exe(),
+            python_script: zstd.totk_config.get_python_script(),
             create_no_window: 0x08000000,
             data: new_data,
             baev_data: baev_data,
             name: name.to_string(),
+            zs_dict: zs_dict,
         })
     }
 
@@ -185,114 +184,43 @@ impl<'a> Asb_py<'a> {
         let mut data = self.text_to_binary(&text)?;
         if file_path.to_lowercase().ends_with(".zs") {
             // data = self.zstd.compressor.compress_zs(&data)?;
-            data = self.zstd.compress_zs(&data)?;
+            data = self.zstd.compress_file(&data, file_path, TotkFileType::ASB, None)?;
         }
         let mut f_handle = std::fs::File::create(file_path)?;
         f_handle.write_all(&data)?;
         Ok(())
     }
 
+    pub fn pool(&self) -> Arc<WorkerPool> {
+        WorkerPool::shared(&self.python_exe, &[self.python_script.clone()])
+    }
+
     pub fn binary_to_text(&self) -> io::Result<String> {
-        let mut child = Command::new(&self.python_exe)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg("asb_binary_to_text")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        
         let mut data = self.data.clone();
         data.extend(ASB_SEPARATOR);
         data.extend(&self.baev_data);
 
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(&data)?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-        // write_string_to_file("stderr.log", &stderr)?;
-        // write_string_to_file("stdout.log", &stdout)?;
-        if stdout.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stdout));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
-
-        if output.status.success() {
-            // println!("Script executed successfully.");
-            eprintln!(
-                "Script execution successfully. {:#?}\n{}",
-                output.status,
-                String::from_utf8_lossy(&output.stderr).into_owned()
-            );
-        } else {
-            eprintln!("Script execution failed. {:#?}\n{}", output.status, &stderr);
-            // eprintln!("Data: {:?}", &stdout);
-            let e = format!(
-                "Script execution failed. Unable to convert asb binary to text. \n{:#?}\n{}",
-                output.status, &stderr
-            );
-            return Err(io::Error::new(io::ErrorKind::Other, e));
-        }
-        Ok(stdout)
+        let stdout = self.pool().call("asb_binary_to_text", &data)?;
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
     }
 
     pub fn text_to_binary(&self, text: &str) -> io::Result<Vec<u8>> {
-        let mut child = Command::new(&self.python_exe)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg("asb_text_to_binary")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(text.as_bytes())?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        // write_string_to_file("stderr.log", &stderr)?;
-
-        if output.stdout.starts_with(b"Error") {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                String::from_utf8_lossy(&output.stdout).into_owned(),
-            ));
-        }
-        if !is_asb(&output.stdout) {
+        let data = self.pool().call("asb_text_to_binary", text.as_bytes())?;
+        if !is_asb(&data) {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
                 "Output is not an ASB file.",
             ));
         }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
-
-        // write_string_to_file("stdout.log", &stdout)?;
-        if output.status.success() {
-            println!("Script executed successfully.");
-        } else {
-            eprintln!("Script execution failed.");
-            let e = format!(
-                "Script execution failed. Unable to convert asb text to binary. \n{:#?}\n{}",
-                output.status, &stderr
-            );
-            return Err(io::Error::new(io::ErrorKind::Other, e));
-        }
-        Ok(output.stdout)
+        println!("Script executed successfully.");
+        Ok(data)
     }
 
     pub fn test_winpython(&self) -> io::Result<()> {
         // env::set_var("PATH", self.newpath.clone());
         let output = Command::new(&self.python_exe)
             .arg(&self.python_script)
-            .creation_flags(self.create_no_window)
+            .no_window(self.create_no_window)
             // .arg("-V")
             .output()?;
         if output.status.success() {
@@ -321,6 +249,7 @@ impl<'a> Asb_py<'a> {
                     println!(" yes!");
                     opened_file.path = Pathlib::new(path_ref);
                     opened_file.file_type = TotkFileType::ASB;
+                    opened_file.zs_dict = asb.zs_dict;
                     data.status_text = format!("Opened: {}", &opened_file.path.full_path);
                     data.path = Pathlib::new(path_ref);
                     data.text = text;
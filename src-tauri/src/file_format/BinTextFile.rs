@@ -2,7 +2,7 @@
 use crate::file_format::TagProduct::TagProduct;
 use crate::Open_and_Save::SendData;
 use crate::Settings::Pathlib;
-use crate::Zstd::{is_byml, is_gamedatalist, TotkFileType, TotkZstd};
+use crate::Zstd::{is_byml, is_gamedatalist, is_zstd, TotkFileType, TotkZstd, ZsDicType};
 use msbt_bindings_rs::MsbtCpp::MsbtCpp;
 use regex::Regex;
 use roead::byml::Byml;
@@ -22,6 +22,7 @@ use super::Rstb::Restbl;
 pub struct FileData {
     pub file_type: TotkFileType,
     pub data: Vec<u8>,
+    pub zs_dict: Option<ZsDicType>, //dictionary the data was compressed with
 }
 
 impl FileData {
@@ -29,6 +30,7 @@ impl FileData {
         Self {
             file_type: TotkFileType::None,
             data: Vec::new(),
+            zs_dict: None,
         }
     }
 }
@@ -61,26 +63,7 @@ impl<'a> BymlFile<'_> {
             Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "")),
         }
         if path.to_ascii_lowercase().ends_with(".zs") {
-            match self.file_data.file_type {
-                TotkFileType::Byml => {
-                    data = self
-                        .zstd
-                        .compress_zs(&data)
-                        .expect("Failed to compress with zs");
-                }
-                TotkFileType::Bcett => {
-                    data = self
-                        .zstd
-                        .compress_bcett(&data)
-                        .expect("Failed to compress with bcett");
-                }
-                _ => {
-                    data = self
-                        .zstd
-                        .compress_zs(&data)
-                        .expect("Failed to compress with zs");
-                }
-            }
+            data = self.zstd.compress_file(&data, &path, self.file_type, self.file_data.zs_dict)?;
         }
         //f_handle.write_all(&data);
         bytes_to_file(data, &path)?;
@@ -167,40 +150,11 @@ impl<'a> BymlFile<'_> {
             data.data = buffer;
             data.file_type = TotkFileType::Byml;
             return Ok(data);
-        } else {
-            match zstd.decompress_zs(&buffer) {
-                //regular byml file compressed with zs
-                Ok(res) => {
-                    if is_byml(&res) {
-                        data.data = res;
-                        data.file_type = TotkFileType::Byml;
-                    }
-                }
-                Err(_err) => {}
-            }
-        }
-        if !is_byml(&data.data) {
-            match zstd.decompress_bcett(&buffer) {
-                //bcett map file
-                Ok(res) => {
-                    data.data = res;
-                    data.file_type = TotkFileType::Byml;
-                    if is_byml(&data.data) {
-                        data.file_type = TotkFileType::Bcett;
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        if !is_byml(&data.data) {
-            match zstd.try_decompress(&buffer) {
-                //try decompressing with other dicts
-                Ok(res) => {
-                    data.data = res;
-                    data.file_type = TotkFileType::Other;
-                }
-                Err(_err) => {}
+        } else if is_zstd(&buffer) {
+            //dictionary from the frame header, falls back to trying each one
+            if let Ok((res, dict)) = zstd.decompress_detect(&buffer) {
+                data.data = res;
+                data.zs_dict = Some(dict);
             }
         }
         if is_byml(&data.data) {
@@ -411,6 +365,20 @@ pub fn bytes_to_file(data: Vec<u8>, path: &str) -> io::Result<()> {
     Ok(())
 }
 
+// MsbtCpp comes from the C++ msbt bindings, which don't mark it Send. What it keeps is plain Rust data
+// copied out of the C++ side (text, binary, endian), nothing pointing back into C++ memory: it has no
+// Drop (get_string_from_data and the Msbt save path move those fields out of it), so it can move to another thread
+pub struct MsbtHandle(pub MsbtCpp);
+
+unsafe impl Send for MsbtHandle {}
+
+impl std::ops::Deref for MsbtHandle {
+    type Target = MsbtCpp;
+    fn deref(&self) -> &MsbtCpp {
+        &self.0
+    }
+}
+
 //#[derive(Serialise, Deserialise)]
 pub struct OpenedFile<'a> {
     pub file_type: TotkFileType,
@@ -418,11 +386,12 @@ pub struct OpenedFile<'a> {
     pub byml: Option<BymlFile<'a>>,
     pub endian: Option<roead::Endian>,
     // pub msyt: Option<MsbtFile>,
-    pub msyt: Option<MsbtCpp>,
+    pub msyt: Option<MsbtHandle>,
     pub aamp: Option<()>,
     pub tag: Option<TagProduct<'a>>,
     pub restbl: Option<Restbl<'a>>,
     pub esetb: Option<Esetb<'a>>,
+    pub zs_dict: Option<ZsDicType>, //detected on open, reused when saving as .zs
 }
 
 impl Default for OpenedFile<'_> {
@@ -437,6 +406,7 @@ impl Default for OpenedFile<'_> {
             tag: None,
             restbl: None,
             esetb: None,
+            zs_dict: None,
         }
     }
 }
@@ -454,11 +424,12 @@ impl<'a> OpenedFile<'_> {
             path: Pathlib::new(path),
             byml: None,
             endian: endian,
-            msyt: msyt,
+            msyt: msyt.map(MsbtHandle),
             aamp: None,
             tag: None,
             restbl: None,
             esetb: None,
+            zs_dict: None,
         }
     }
 
@@ -473,6 +444,7 @@ impl<'a> OpenedFile<'_> {
             tag: None,
             restbl: None,
             esetb: None,
+            zs_dict: None,
         }
     }
 
@@ -484,6 +456,7 @@ impl<'a> OpenedFile<'_> {
         self.msyt = None;
         self.aamp = None;
         self.tag = None;
+        self.zs_dict = None;
     }
 
     pub fn get_endian_label(&self) -> String {
@@ -1,7 +1,7 @@
 #![allow(non_snake_case,non_camel_case_types)]
 use std::{io, path::Path, sync::Arc};
 use roead::byml::Byml;
-use crate::{Open_and_Save::SendData, Settings::Pathlib, Zstd::{is_esetb_path, TotkFileType, TotkZstd}};
+use crate::{Open_and_Save::SendData, Settings::Pathlib, TotkConfig::TotkConfig, Zstd::{is_esetb_path, TotkFileType, TotkZstd}};
 use super::{BinTextFile::{BymlFile, FileData, OpenedFile}, Wrapper::PythonWrapper};
 
 const PTCL_JSON_KEY: &str = "PTCL_JSON";
@@ -17,7 +17,7 @@ pub struct Esetb<'a> {
 #[allow(dead_code)]
 impl<'a> Esetb<'a> {
     pub fn from_binary(data: &Vec<u8>, zstd: Arc<TotkZstd<'a>>) -> io::Result<Esetb<'a>> {
-        let file_data = FileData {file_type: TotkFileType::Esetb, data: data.to_vec()};
+        let file_data = FileData {file_type: TotkFileType::Esetb, data: data.to_vec(), zs_dict: None};
         let pio = Byml::from_binary(data).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         let mut byml = BymlFile {
             endian: BymlFile::get_endiannes(&file_data.data),
@@ -27,7 +27,7 @@ impl<'a> Esetb<'a> {
             zstd: zstd.clone(),
             file_type: TotkFileType::Byml,
         };
-        let ptcl = Self::process_ptcl_binary(&mut byml.pio)?;
+        let ptcl = Self::process_ptcl_binary(&mut byml.pio, &zstd.totk_config)?;
         Ok(Esetb { byml: byml, ptcl: ptcl })
     }
 
@@ -35,10 +35,10 @@ impl<'a> Esetb<'a> {
         self.byml.pio.to_binary(roead::Endian::Little)
     }
 
-    pub fn process_ptcl_binary(pio: &mut Byml) -> io::Result<Vec<u8>> {
+    pub fn process_ptcl_binary(pio: &mut Byml, totk_config: &TotkConfig) -> io::Result<Vec<u8>> {
         // let endian = roead::Endian::Little;
         let mut result: Vec<u8> = Vec::new();
-        let py_wrap = PythonWrapper::default();
+        let py_wrap = PythonWrapper::from_config(totk_config);
         let pio_map = pio.as_mut_map().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         if !pio_map.contains_key(PTCL_BIN_KEY)  {
             return Err(io::Error::new(io::ErrorKind::Other, "BYML file does not contain PtclBin key"));
@@ -76,7 +76,7 @@ impl<'a> Esetb<'a> {
     pub fn from_file<P:AsRef<Path>>(file: P, zstd: Arc<TotkZstd<'a>>) -> io::Result<Esetb<'a>> {
         if let Some(byml) = BymlFile::new(file.as_ref(), zstd.clone()) {
             let mut esetb = Esetb { byml: byml, ptcl: Vec::new() };
-            match Self::process_ptcl_binary(&mut esetb.byml.pio) {
+            match Self::process_ptcl_binary(&mut esetb.byml.pio, &zstd.totk_config) {
                 Ok(ptcl) => esetb.ptcl = ptcl,
                 Err(e) => {
                     println!("Error while reading PtclBin key: {}", e);
@@ -107,7 +107,7 @@ impl<'a> Esetb<'a> {
     }
 
     pub fn update_from_text(&mut self, text: &str) -> io::Result<()> {
-        let py_wrap = PythonWrapper::default();
+        let py_wrap = PythonWrapper::from_config(&self.byml.zstd.totk_config);
         self.byml.pio = Byml::from_text(text).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         self.remove_ptclbin_entry()?;
         if let Ok(pio_map) = self.byml.pio.as_mut_map() {
@@ -178,6 +178,7 @@ impl<'a> Esetb<'a> {
                 opened_file.path = Pathlib::new(path_ref);
                 opened_file.endian = esetb.byml.endian;
                 opened_file.file_type = TotkFileType::Esetb;
+                opened_file.zs_dict = esetb.byml.file_data.zs_dict;
                 data.status_text = format!("Opened {}", path_ref.display());
                 data.path = Pathlib::new(path_ref);
                 data.text = esetb.to_string();
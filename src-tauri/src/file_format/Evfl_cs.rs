@@ -17,7 +17,7 @@ impl<'a> Evfl<'a> {
     pub fn new(zstd: Arc<TotkZstd<'a>>) -> Evfl<'a> {
         Self {
             zstd: zstd.clone(),
-            wrapper: ExeWrapper::dotnet_new(),
+            wrapper: ExeWrapper::dotnet_from_config(&zstd.totk_config),
             data: Vec::new(),
         }
     }
@@ -56,7 +56,8 @@ impl<'a> Evfl<'a> {
         let path_ref = path.as_ref();
         print!("Is {:?} a evfl? ", &path_ref);
         let evfl = Evfl::new(zstd.clone());
-        if let Ok(text) = evfl.binary_file_to_string( path_ref) {
+        let (rawdata, zs_dict) = zstd.decompress_if_zstd(std::fs::read(path_ref).ok()?).ok()?;
+        if let Ok(text) = evfl.binary_to_string(&rawdata) {
             let mut opened_file = OpenedFile::default();
             let mut data = SendData::default();
             let pathlib_var = Pathlib::new(path_ref);
@@ -66,6 +67,7 @@ impl<'a> Evfl<'a> {
             opened_file.path = pathlib_var.clone();
             opened_file.endian = Some(roead::Endian::Little);
             opened_file.file_type = TotkFileType::Evfl;
+            opened_file.zs_dict = zs_dict;
             data.status_text = format!("Opened {}", path_ref.display());
             data.path = pathlib_var;
             data.text = text;  
@@ -2,7 +2,7 @@
 use std::path::Path;
 use std::sync::Arc;
 use std::io::{self, Read, Write};
-use crate::Zstd::{is_evfl, TotkZstd};
+use crate::Zstd::{is_evfl, TotkFileType, TotkZstd};
 use super::Wrapper::PythonWrapper;
 
 
@@ -18,7 +18,7 @@ impl<'a> Evfl_py<'a> {
     pub fn new(zstd: Arc<TotkZstd<'a>>) -> Evfl_py<'a> {
         Self {
             zstd: zstd.clone(),
-            py_wrapper: PythonWrapper::default(),
+            py_wrapper: PythonWrapper::from_config(&zstd.totk_config),
             data: Vec::new(),
         }
     }
@@ -44,7 +44,7 @@ impl<'a> Evfl_py<'a> {
         Ok(
             Self {
                 zstd: zstd.clone(),
-                py_wrapper: PythonWrapper::default(),
+                py_wrapper: PythonWrapper::from_config(&zstd.totk_config),
                 data: new_data,
             }
         )
@@ -88,7 +88,7 @@ impl<'a> Evfl_py<'a> {
         let mut data = self.text_to_binary(&text)?;
         if path_ref.to_string_lossy().to_lowercase().ends_with(".zs") {
             // data = self.zstd.compressor.compress_zs(&data)?;
-            data = self.zstd.compress_zs(&data)?;
+            data = self.zstd.compress_file(&data, path_ref, TotkFileType::Evfl, None)?;
         }
         let mut f_handle = std::fs::File::create(file_path)?;
         f_handle.write_all(&data)?;
@@ -0,0 +1,86 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use std::io;
+
+// Editable hex view for binary data nothing else can parse, one line per 16 bytes:
+//   00000010: 59 42 07 00 10 00 00 00 20 00 00 00 C2 01 00 00  |YB...... .......|
+// Only the bytes between the offset and the ascii column are read back, offsets are not checked,
+// so lines can be added or removed freely.
+const BYTES_PER_LINE: usize = 16;
+
+pub fn to_hex_dump(data: &[u8]) -> String {
+    let mut res = String::with_capacity(data.len() * 4 + data.len() / BYTES_PER_LINE * 12);
+    for (i, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        res.push_str(&format!(
+            "{:08X}: {:<width$}  |{}|\n",
+            i * BYTES_PER_LINE,
+            hex.join(" "),
+            ascii,
+            width = BYTES_PER_LINE * 3 - 1
+        ));
+    }
+    res
+}
+
+pub fn from_hex_dump(text: &str) -> io::Result<Vec<u8>> {
+    let mut res = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        //ascii column first, it may contain ':' of its own
+        let bytes = line.split('|').next().unwrap_or_default();
+        let bytes = match bytes.split_once(':') {
+            Some((offset, rest))
+                if !offset.trim().is_empty()
+                    && offset.trim().chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                rest
+            }
+            _ => bytes,
+        };
+        for byte in bytes.split_whitespace() {
+            let value = u8::from_str_radix(byte, 16).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid hex byte \"{}\" in line {}", byte, line_no + 1),
+                )
+            })?;
+            res.push(value);
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..=255u8).chain(b"a:b|c::||:".iter().copied()).collect();
+        assert_eq!(from_hex_dump(&to_hex_dump(&data)).unwrap(), data);
+        assert!(from_hex_dump("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn ascii_column_with_separators() {
+        //':' and '|' in the ascii column, with and without the offset
+        let line = "00000000: 3A 7C 41 3A                                      |:|A:|";
+        assert_eq!(from_hex_dump(line).unwrap(), b":|A:");
+        assert_eq!(from_hex_dump(&line[10..]).unwrap(), b":|A:");
+        assert_eq!(from_hex_dump("3A 7C  |:|\n# comment\n41").unwrap(), b":|A");
+    }
+
+    #[test]
+    fn invalid_byte_reports_line() {
+        let err = from_hex_dump("00\n00000010: 0G").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -1,9 +1,8 @@
 #![allow(non_snake_case, non_camel_case_types)]
-use flate2::read::ZlibDecoder;
 use roead;
 use roead::sarc::{Sarc, SarcWriter};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
@@ -11,31 +10,37 @@ use std::sync::Arc;
 
 //mod Zstd;
 
+use crate::Minimize::{MinimizeReport, MinimizedEntry};
 use crate::Settings::{makedirs, Pathlib};
 use crate::TotkConfig::TotkConfig;
-use crate::Zstd::{is_sarc, is_sarc_root_path, sha256, TotkFileType, TotkZstd};
+use crate::Zstd::{
+    get_internal_filepaths_data, is_sarc, is_sarc_root_path, is_zstd, sha256, TotkFileType, TotkZstd, ZsDicType,
+};
 
 // use super::SarcEntriesData::get_sarc_entries_data;
 
-pub fn get_sarc_entries_data() -> io::Result<HashMap<String, String>> {
-    //parse json
-    println!("Getting global sarc data ");
-    let json_zlibdata = fs::read("bin/totk_sarc_sha256.bin")?;
-    let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
-    let mut json_str = String::new();
-    decoder.read_to_string(&mut json_str)?;
-    let res: HashMap<String, String> = serde_json::from_str(&json_str)?;
-    Ok(res)
+pub fn get_sarc_entries_data(config: &TotkConfig) -> io::Result<Arc<HashMap<String, String>>> {
+    config.index.sarc_hashes(config)
+}
+
+// Archive a nested one was opened from, kept until the nested one is closed
+pub struct ParentArchive<'a> {
+    pub pack: PackFile<'a>,
+    pub vanila: Option<PackFile<'a>>,
+    pub internal_path: String, //path of the nested archive inside `pack`
 }
 
 pub struct PackComparer<'a> {
     pub opened: Option<PackFile<'a>>,
     pub vanila: Option<PackFile<'a>>,
+    //archives above the opened one, first is the file opened from disk. Empty unless nested
+    pub parents: Vec<ParentArchive<'a>>,
     // pub totk_config: Arc<TotkConfig>,
     pub zstd: Arc<TotkZstd<'a>>,
     pub added: HashMap<String, String>,
     pub modded: HashMap<String, String>,
-    pub global_sarc_data: HashMap<String, String>,
+    pub removed: HashSet<String>, //vanilla entries missing from the opened pack
+    pub global_sarc_data: Arc<HashMap<String, String>>, //shared with the config index
 }
 
 #[allow(dead_code)]
@@ -52,14 +57,22 @@ impl<'a> PackComparer<'a> {
                 }
             }
         }
+        //any other archive by its name in the romfs. Loaded once here, every compare reuses it
+        if vanila.is_none() && zstd.is_valid() {
+            if let Ok(vanila_path) = config.find_vanila_file_in_romfs(&pack.path.full_path) {
+                vanila = PackFile::new(vanila_path, zstd.clone()).ok();
+            }
+        }
         let mut pack = Self {
             opened: Some(pack),
             vanila: vanila,
+            parents: Vec::new(),
             // totk_config: config,
             zstd: zstd.clone(),
             added: HashMap::default(),
             modded: HashMap::default(),
-            global_sarc_data: HashMap::default(),
+            removed: HashSet::default(),
+            global_sarc_data: Arc::default(),
         };
         println!("Comparing and reloading");
         pack.compare_and_reload();
@@ -145,8 +158,229 @@ impl<'a> PackComparer<'a> {
     //     ));
     // }
 
+    pub fn is_nested(&self) -> bool {
+        !self.parents.is_empty()
+    }
+
+    // Opens an archive stored in the opened one (plain, Yaz0 or zstd), the opened one becomes its parent
+    pub fn open_nested(&mut self, internal_path: &str) -> io::Result<()> {
+        let opened = self
+            .opened
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No opened pack"))?;
+        let data = opened.writer.get_file(internal_path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} absent in {}", internal_path, &opened.path.name))
+        })?;
+        let nested = PackFile::from_data(internal_path, data.to_vec(), self.zstd.clone())?;
+        let nested_vanila = self
+            .vanila
+            .as_ref()
+            .and_then(|vanila| vanila.sarc.get_data(internal_path))
+            .and_then(|data| PackFile::from_data(internal_path, data.to_vec(), self.zstd.clone()).ok());
+        if let Some(parent) = self.opened.take() {
+            self.parents.push(ParentArchive {
+                pack: parent,
+                vanila: self.vanila.take(),
+                internal_path: internal_path.to_string(),
+            });
+        }
+        self.opened = Some(nested);
+        self.vanila = nested_vanila;
+        self.compare_and_reload();
+        Ok(())
+    }
+
+    // Writes the opened nested archive back into its parent, compressed the way it was
+    pub fn commit_nested(&mut self) -> io::Result<()> {
+        if let (Some(opened), Some(parent)) = (&self.opened, self.parents.last_mut()) {
+            let data = opened.to_nested_binary()?;
+            Self::commit_to_parent(parent, data);
+        }
+        Ok(())
+    }
+
+    // Writing a nested archive is an undoable operation of the parent
+    fn commit_to_parent(parent: &mut ParentArchive<'a>, data: Vec<u8>) {
+        let mut op = SarcOp::new(format!("Edit {}", &parent.internal_path));
+        parent.pack.add_file_recorded(&mut op, &parent.internal_path, data);
+        parent.pack.push_op(op);
+    }
+
+    // Propagates the changes of every nested level up to the file opened from disk
+    pub fn commit_all(&mut self) -> io::Result<()> {
+        self.commit_nested()?;
+        for i in (1..self.parents.len()).rev() {
+            let (upper, lower) = self.parents.split_at_mut(i);
+            let parent = &mut upper[i - 1];
+            let data = lower[0].pack.to_nested_binary()?;
+            Self::commit_to_parent(parent, data);
+        }
+        Ok(())
+    }
+
+    // Back to the parent archive, the changes stay in the parent
+    pub fn close_nested(&mut self) -> io::Result<()> {
+        self.commit_nested()?;
+        if let Some(parent) = self.parents.pop() {
+            self.opened = Some(parent.pack);
+            self.vanila = parent.vanila;
+            self.compare_and_reload();
+        }
+        Ok(())
+    }
+
+    // Closes nested archives down to `level`, 0 is the file opened from disk
+    pub fn close_nested_to(&mut self, level: usize) -> io::Result<()> {
+        while self.parents.len() > level {
+            self.close_nested()?;
+        }
+        Ok(())
+    }
+
+    // The archive opened from disk, saved when any level is saved
+    pub fn root_pack_mut(&mut self) -> Option<&mut PackFile<'a>> {
+        match self.parents.first_mut() {
+            Some(root) => Some(&mut root.pack),
+            None => self.opened.as_mut(),
+        }
+    }
+
+    // File name of the archive opened from disk, then the path of every nested archive
+    pub fn breadcrumbs(&self) -> Vec<String> {
+        let mut res: Vec<String> = Vec::new();
+        match self.parents.first() {
+            Some(root) => res.push(root.pack.path.name.clone()),
+            None => {
+                if let Some(opened) = &self.opened {
+                    res.push(opened.path.name.clone());
+                }
+            }
+        }
+        res.extend(self.parents.iter().map(|p| p.internal_path.clone()));
+        res
+    }
+
+    // Entries of the opened pack (or removed from it) at `internal_path`, every file below it for a directory
+    pub fn entries_at(&self, internal_path: &str) -> Vec<String> {
+        let opened = match &self.opened {
+            Some(opened) => opened,
+            None => return Vec::new(),
+        };
+        if opened.writer.files.contains_key(internal_path) || self.removed.contains(internal_path) {
+            return vec![internal_path.to_string()];
+        }
+        let dir = format!("{}/", internal_path.trim_end_matches('/'));
+        let mut res: Vec<String> = opened
+            .writer
+            .files
+            .keys()
+            .chain(self.removed.iter())
+            .filter(|file| internal_path.is_empty() || file.starts_with(&dir))
+            .cloned()
+            .collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    // Vanilla bytes of `entries`, from the vanilla pack, else from the romfs pack listed for each entry (custom actors).
+    // Entries without vanilla version are left out
+    pub fn vanila_entries_data(&self, entries: &[String]) -> HashMap<String, Vec<u8>> {
+        let mut res: HashMap<String, Vec<u8>> = HashMap::default();
+        let mut internal_filepaths: Option<Arc<HashMap<String, String>>> = None;
+        let mut romfs_packs: HashMap<String, Option<PackFile<'a>>> = HashMap::default();
+        for entry in entries {
+            if let Some(data) = self.vanila.as_ref().and_then(|v| v.sarc.get_data(entry)) {
+                res.insert(entry.clone(), data.to_vec());
+                continue;
+            }
+            if !self.zstd.is_valid() {
+                continue;
+            }
+            if internal_filepaths.is_none() {
+                internal_filepaths = Some(get_internal_filepaths_data(&self.zstd.totk_config).unwrap_or_default());
+            }
+            let sarc_path = internal_filepaths
+                .as_ref()
+                .and_then(|paths| self.zstd.find_vanila_internal_file_path_with(paths, entry).ok());
+            let data = sarc_path.and_then(|sarc_path| {
+                romfs_packs
+                    .entry(sarc_path.clone())
+                    .or_insert_with(|| PackFile::new(&sarc_path, self.zstd.clone()).ok())
+                    .as_ref()
+                    .and_then(|pack| pack.sarc.get_data(entry).map(|d| d.to_vec()))
+            });
+            if let Some(data) = data {
+                res.insert(entry.clone(), data);
+            }
+        }
+        res
+    }
+
+    // Replaces the entries at `internal_path` with their vanilla bytes, removed entries are added back.
+    // Returns the restored entries and the ones without vanilla version
+    pub fn restore_vanila(&mut self, internal_path: &str) -> io::Result<(Vec<String>, Vec<String>)> {
+        let entries = self.entries_at(internal_path);
+        if entries.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} absent in the opened pack", internal_path)));
+        }
+        let vanila_data = self.vanila_entries_data(&entries);
+        let (restored, not_vanila): (Vec<String>, Vec<String>) =
+            entries.into_iter().partition(|entry| vanila_data.contains_key(entry));
+        if let Some(opened) = &mut self.opened {
+            let mut op = SarcOp::new(format!("restore {}", internal_path));
+            for entry in restored.iter() {
+                if let Some(data) = vanila_data.get(entry) {
+                    opened.add_file_recorded(&mut op, entry, data.clone());
+                }
+            }
+            opened.push_op(op);
+        }
+        self.compare_and_reload();
+        Ok((restored, not_vanila))
+    }
+
+    // Entries compare() found neither added nor modded, so same sha256 as vanilla. None for custom packs
+    pub fn unmodified_entries(&self) -> Vec<MinimizedEntry> {
+        let opened = match &self.opened {
+            Some(opened) => opened,
+            None => return Vec::new(),
+        };
+        if self.added.len() == opened.hashes.len() {
+            return Vec::new();
+        }
+        opened
+            .writer
+            .files
+            .iter()
+            .filter(|(file, _)| !self.added.contains_key(*file) && !self.modded.contains_key(*file))
+            .map(|(file, data)| MinimizedEntry {
+                path: file.to_string(),
+                size: data.len(),
+            })
+            .collect()
+    }
+
+    // Strips the entries identical to vanilla as one undoable operation, a dry run only lists them
+    pub fn minimize(&mut self, dry_run: bool) -> MinimizeReport {
+        let report = MinimizeReport::new(dry_run, self.unmodified_entries());
+        if dry_run || report.entries.is_empty() {
+            return report;
+        }
+        if let Some(opened) = &mut self.opened {
+            let mut op = SarcOp::new("minimize".to_string());
+            for entry in report.entries.iter() {
+                opened.remove_file_recorded(&mut op, &entry.path);
+            }
+            opened.push_op(op);
+        }
+        self.compare_and_reload();
+        report
+    }
+
     pub fn get_sarc_paths(&self) -> SarcPaths {
         let mut paths = SarcPaths::default();
+        paths.breadcrumbs = self.breadcrumbs();
         if let Some(opened) = &self.opened {
             for file in opened.sarc.files() {
                 if let Some(name) = file.name {
@@ -159,6 +393,8 @@ impl<'a> PackComparer<'a> {
             for (path, _) in self.modded.iter() {
                 paths.modded_paths.push(path.to_string());
             }
+            paths.removed_paths = self.removed.iter().cloned().collect();
+            paths.removed_paths.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
             let size = paths.paths.len();
             if size == paths.added_paths.len() {
                 paths.added_paths.clear();
@@ -188,7 +424,9 @@ impl<'a> PackComparer<'a> {
                 println!("Comparing vanila actor");
                 let mut added: HashMap<String, String> = HashMap::default();
                 let mut modded: HashMap<String, String> = HashMap::default();
-                vanila.self_populate_hashes();
+                if vanila.hashes.is_empty() {
+                    vanila.self_populate_hashes(); //vanilla never changes, hashed on the first compare only
+                }
                 for (file, hash) in opened.hashes.iter() {
                     let van_hash = vanila.hashes.get(file);
                     match van_hash {
@@ -204,6 +442,12 @@ impl<'a> PackComparer<'a> {
                 }
                 self.added = added;
                 self.modded = modded;
+                self.removed = vanila
+                    .hashes
+                    .keys()
+                    .filter(|file| !opened.hashes.contains_key(*file))
+                    .cloned()
+                    .collect();
                 is_compared = self.added.len() != opened.hashes.keys().len();
                 // println!("Added {:?}\nModded {:?}", self.added.keys(), self.modded.keys());
             }
@@ -211,7 +455,7 @@ impl<'a> PackComparer<'a> {
                 //custom actor
                 println!("Comparing custom actor");
                 if self.global_sarc_data.is_empty() {
-                    self.global_sarc_data = get_sarc_entries_data().unwrap_or_default();
+                    self.global_sarc_data = get_sarc_entries_data(&self.zstd.totk_config).unwrap_or_default();
                 }
                 let mut added: HashMap<String, String> = HashMap::default();
                 let mut modded: HashMap<String, String> = HashMap::default();
@@ -230,11 +474,32 @@ impl<'a> PackComparer<'a> {
                 }
                 self.added = added;
                 self.modded = modded;
+                self.removed = self.removed_from_global_data(opened);
                 // println!("Added {:?}\nModded {:?}", self.added, self.modded);
             }
         }
     }
 
+    // Fallback when the romfs has no vanilla pack to compare with (compare uses its entries otherwise).
+    // The global data has no pack membership, an entry is counted as part of the pack when its file name
+    // starts with the pack name (Actor/<name>.engine__actor__ActorParam.bgyml), so it only sees actor packs
+    fn removed_from_global_data(&self, opened: &PackFile) -> HashSet<String> {
+        let stem = opened.path.stem.to_lowercase();
+        if stem.is_empty() || self.added.len() == opened.hashes.len() {
+            return HashSet::default(); //custom pack, nothing in vanilla
+        }
+        let prefix = format!("{}.", stem);
+        self.global_sarc_data
+            .keys()
+            .filter(|file| !opened.hashes.contains_key(*file))
+            .filter(|file| {
+                let name = file.rsplit('/').next().unwrap_or_default().to_lowercase();
+                name.starts_with(&prefix)
+            })
+            .cloned()
+            .collect()
+    }
+
     pub fn get_vanila_mals(path: &Pathlib, zstd: Arc<TotkZstd<'a>>) -> Option<PackFile<'a>> {
         println!("Getting the mals: {}", &path.stem);
         // let versions: Vec<usize> = (100..130).collect();//130, in case new updates are issued in the future for TOTK
@@ -299,6 +564,35 @@ impl<'a> PackComparer<'a> {
     }
 }
 
+// Undo history of the writer. Every operation keeps only the files it touched, before and after
+const MAX_SARC_HISTORY: usize = 64;
+
+pub struct SarcChange {
+    pub path: String,
+    pub before: Option<Vec<u8>>, //None: file did not exist
+    pub after: Option<Vec<u8>>,  //None: file removed
+}
+
+pub struct SarcOp {
+    pub description: String,
+    pub changes: Vec<SarcChange>,
+}
+
+impl SarcOp {
+    pub fn new(description: String) -> Self {
+        Self {
+            description,
+            changes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SarcHistory {
+    pub undo: Vec<SarcOp>,
+    pub redo: Vec<SarcOp>,
+}
+
 pub struct PackFile<'a> {
     pub path: Pathlib,
     pub totk_config: Arc<TotkConfig>,
@@ -310,6 +604,8 @@ pub struct PackFile<'a> {
     pub hashes: HashMap<String, String>,
     pub sarc: Sarc<'a>,
     pub is_yaz0: bool,
+    pub zs_dict: Option<ZsDicType>, //dictionary of the .zs file, reused on save
+    pub history: SarcHistory,
 }
 
 #[allow(dead_code)]
@@ -329,6 +625,8 @@ impl<'a> PackFile<'_> {
             hashes: HashMap::default(),
             sarc: sarc,
             is_yaz0: false,
+            zs_dict: None,
+            history: SarcHistory::default(),
         })
     }
 
@@ -348,6 +646,29 @@ impl<'a> PackFile<'_> {
         Ok(pack)
     }
 
+    // Archive stored inside another sarc, `internal_path` becomes its path
+    pub fn from_data(internal_path: &str, data: Vec<u8>, zstd: Arc<TotkZstd<'a>>) -> io::Result<PackFile<'a>> {
+        let mut pack = Self::default(zstd.clone())?;
+        let try_zstd = is_zstd(&data);
+        pack.sarc_data_to_sarc(data, try_zstd)?;
+        pack.writer = SarcWriter::from_sarc(&pack.sarc);
+        pack.endian = pack.sarc.endian();
+        pack.path = Pathlib::new(internal_path);
+        Ok(pack)
+    }
+
+    // Binary as stored in the parent archive, compressed the same way it was
+    pub fn to_nested_binary(&self) -> io::Result<Vec<u8>> {
+        let data: Vec<u8> = self.writer.to_binary();
+        if self.zs_dict.is_some() {
+            return self.compress(&data, &self.path.full_path);
+        }
+        if self.is_yaz0 {
+            return Ok(roead::yaz0::compress(&data));
+        }
+        Ok(data)
+    }
+
     pub fn rename(&mut self, old_name: &str, new_name: &str) -> io::Result<()> {
         let some_data = self.writer.get_file(old_name);
         match some_data {
@@ -366,6 +687,82 @@ impl<'a> PackFile<'_> {
         Ok(())
     }
 
+    // Adds or replaces a file, the change is kept in `op`
+    pub fn add_file_recorded(&mut self, op: &mut SarcOp, path: &str, data: Vec<u8>) {
+        let before = self.writer.get_file(path).map(|d| d.to_vec());
+        if before.as_ref() == Some(&data) {
+            return;
+        }
+        self.writer.add_file(path, data.clone());
+        op.changes.push(SarcChange {
+            path: path.to_string(),
+            before,
+            after: Some(data),
+        });
+    }
+
+    // Removes a file, the change is kept in `op`
+    pub fn remove_file_recorded(&mut self, op: &mut SarcOp, path: &str) {
+        if let Some(before) = self.writer.get_file(path).map(|d| d.to_vec()) {
+            self.writer.remove_file(path);
+            op.changes.push(SarcChange {
+                path: path.to_string(),
+                before: Some(before),
+                after: None,
+            });
+        }
+    }
+
+    // Adds a finished operation to the history, a new operation drops everything that could be redone
+    pub fn push_op(&mut self, op: SarcOp) {
+        if op.changes.is_empty() {
+            return;
+        }
+        self.history.redo.clear();
+        self.history.undo.push(op);
+        if self.history.undo.len() > MAX_SARC_HISTORY {
+            self.history.undo.remove(0);
+        }
+    }
+
+    fn apply_changes(&mut self, op: &SarcOp, is_undo: bool) {
+        //undo goes backwards so a file touched twice ends up as it was first
+        let changes: Box<dyn Iterator<Item = &SarcChange>> = if is_undo {
+            Box::new(op.changes.iter().rev())
+        } else {
+            Box::new(op.changes.iter())
+        };
+        for change in changes {
+            let data = if is_undo { &change.before } else { &change.after };
+            match data {
+                Some(data) => {
+                    self.writer.add_file(&change.path, data.clone());
+                }
+                None => {
+                    self.writer.remove_file(&change.path);
+                }
+            }
+        }
+    }
+
+    // Reverts the last operation, returns its description
+    pub fn undo(&mut self) -> Option<String> {
+        let op = self.history.undo.pop()?;
+        self.apply_changes(&op, true);
+        let description = op.description.clone();
+        self.history.redo.push(op);
+        Some(description)
+    }
+
+    // Applies again the last reverted operation, returns its description
+    pub fn redo(&mut self) -> Option<String> {
+        let op = self.history.redo.pop()?;
+        self.apply_changes(&op, false);
+        let description = op.description.clone();
+        self.history.undo.push(op);
+        Some(description)
+    }
+
     pub fn reload(&mut self) {
         let data: Vec<u8> = self.writer.to_binary();
         self.sarc = Sarc::new(data).expect("Failed");
@@ -393,30 +790,17 @@ impl<'a> PackFile<'_> {
         self.save(dest_file)
     }
 
-    fn compress(&self, data: &Vec<u8>) -> io::Result<Vec<u8>> {
-        // let zstd = ZstdCppCompressor::from_totk_zstd(self.zstd.clone());
-        match self.file_type {
-            TotkFileType::Sarc => {
-                println!("Compressing SARC");
-                // return self.zstd.compressor.compress_pack(data);
-                return self.zstd.compress_pack(data);
-            }
-            TotkFileType::MalsSarc => {
-                println!("Compressing MALS SARC");
-                // return self.zstd.compressor.compress_zs(data);
-                return self.zstd.compress_zs(data);
-            }
-            _ => {
-                return Ok(data.to_vec());
-            }
-        }
+    fn compress(&self, data: &Vec<u8>, dest_file: &str) -> io::Result<Vec<u8>> {
+        //packs and mals archives only differ by rules in the compression policy
+        println!("Compressing {:?}", self.file_type);
+        self.zstd.compress_file(data, dest_file, self.file_type, self.zs_dict)
     }
 
     pub fn save(&mut self, dest_file: String) -> io::Result<()> {
         makedirs(&PathBuf::from(&dest_file))?;
         let mut data: Vec<u8> = self.writer.to_binary();
         if dest_file.to_lowercase().ends_with(".zs") {
-            data = self.compress(&data)?;
+            data = self.compress(&data, &dest_file)?;
         } else if self.is_yaz0 {
             data = roead::yaz0::compress(&data);
         }
@@ -428,6 +812,15 @@ impl<'a> PackFile<'_> {
         let mut f_handle: fs::File = fs::File::open(&path)?;
         let mut buffer: Vec<u8> = Vec::new();
         f_handle.read_to_end(&mut buffer)?;
+        let try_zstd = path
+            .as_ref()
+            .to_string_lossy()
+            .to_lowercase()
+            .ends_with(".zs");
+        self.sarc_data_to_sarc(buffer, try_zstd)
+    }
+
+    fn sarc_data_to_sarc(&mut self, mut buffer: Vec<u8>, try_zstd: bool) -> io::Result<()> {
         if buffer.starts_with(b"Yaz0") {
             if let Ok(dec_data) = roead::yaz0::decompress(&buffer) {
                 buffer = dec_data;
@@ -440,27 +833,15 @@ impl<'a> PackFile<'_> {
                 return Ok(());
             }
         }
-        if path
-            .as_ref()
-            .to_string_lossy()
-            .to_lowercase()
-            .ends_with(".zs")
-        {
-            if let Ok(dec_data) = self.zstd.decompress_pack(&buffer) {
-                if is_sarc(&dec_data) {
-                    // self.data = dec_data;
-                    self.sarc = Sarc::new(dec_data)
-                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-                    self.file_type = TotkFileType::Sarc;
-                    return Ok(());
-                }
-            }
-            if let Ok(dec_data) = self.zstd.decompress_zs(&buffer) {
+        if try_zstd {
+            if let Ok((dec_data, dict)) = self.zstd.decompress_detect(&buffer) {
                 if is_sarc(&dec_data) {
                     // self.data = dec_data;
                     self.sarc = Sarc::new(dec_data)
                         .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-                    self.file_type = TotkFileType::MalsSarc;
+                    //Mals archives use the zs dictionary, packs the pack one
+                    self.file_type = if dict == ZsDicType::Zs {TotkFileType::MalsSarc} else {TotkFileType::Sarc};
+                    self.zs_dict = Some(dict);
                     return Ok(());
                 }
             }
@@ -538,6 +919,11 @@ pub struct SarcPaths {
     pub paths: Vec<String>,
     pub added_paths: Vec<String>,
     pub modded_paths: Vec<String>,
+    #[serde(default)]
+    pub removed_paths: Vec<String>,
+    //opened file, then nested archives down to the one shown
+    #[serde(default)]
+    pub breadcrumbs: Vec<String>,
 }
 impl Default for SarcPaths {
     fn default() -> Self {
@@ -545,6 +931,8 @@ impl Default for SarcPaths {
             paths: Vec::new(),
             added_paths: Vec::new(),
             modded_paths: Vec::new(),
+            removed_paths: Vec::new(),
+            breadcrumbs: Vec::new(),
         }
     }
 }
@@ -1,5 +1,6 @@
 #![allow(non_snake_case, non_camel_case_types)]
 // use std::any;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
@@ -7,7 +8,7 @@ use std::sync::Arc;
 
 use crate::Open_and_Save::SendData;
 use crate::Settings::{list_files_recursively, Pathlib};
-use crate::Zstd::{is_restbl, TotkFileType, TotkZstd};
+use crate::Zstd::{is_restbl, TotkFileType, TotkZstd, ZsDicType};
 use flate2::read::ZlibDecoder;
 use restbl::bin::ResTblReader;
 use restbl::ResourceSizeTable;
@@ -28,6 +29,232 @@ fn get_rstb_data() -> io::Result<Vec<String>> {
     Ok(res)
 }
 
+// Binary layout of the table (RESTBL), little endian:
+//   0x00 "RESTBL", 0x06 u32 version, 0x0A u32 name length (160), 0x0E u32 hash entries, 0x12 u32 name entries
+//   hash entries: u32 CRC32 of the path, u32 size, sorted by hash
+//   name entries: path padded to 160 bytes, u32 size, only for paths whose CRC32 collides with another one
+const RESTBL_MAGIC: &[u8; 6] = b"RESTBL";
+const RESTBL_HEADER_SIZE: usize = 0x16;
+const RESTBL_NAME_SIZE: usize = 160;
+
+pub fn rstb_crc32(name: &str) -> u32 {
+    crc32fast::hash(name.as_bytes())
+}
+
+// Entry typed as a raw hash: 0x followed by up to 8 hex digits
+pub fn parse_rstb_hash(entry: &str) -> Option<u32> {
+    let hex = entry.trim().strip_prefix("0x").or_else(|| entry.trim().strip_prefix("0X"))?;
+    if hex.is_empty() || hex.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+// Known paths by their CRC32, paths sharing one are stored in the name entries
+fn names_by_hash(names: &[String]) -> HashMap<u32, Vec<String>> {
+    let mut res: HashMap<u32, Vec<String>> = HashMap::new();
+    for name in names.iter() {
+        let names = res.entry(rstb_crc32(name)).or_default();
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    res
+}
+
+// Every entry of the table as stored, ResourceSizeTable has no way to list them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RstbEntries {
+    pub hashes: BTreeMap<u32, u32>,
+    pub names: BTreeMap<String, u32>,
+}
+
+fn read_u32(data: &[u8], pos: usize) -> io::Result<u32> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "RSTB truncated"))
+}
+
+impl RstbEntries {
+    pub fn from_binary(data: &[u8]) -> io::Result<RstbEntries> {
+        if !data.starts_with(RESTBL_MAGIC) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a RESTBL file"));
+        }
+        let name_size = read_u32(data, 0x0A)? as usize;
+        let hash_count = read_u32(data, 0x0E)? as usize;
+        let name_count = read_u32(data, 0x12)? as usize;
+        let mut res = RstbEntries::default();
+        let mut pos = RESTBL_HEADER_SIZE;
+        for _ in 0..hash_count {
+            res.hashes.insert(read_u32(data, pos)?, read_u32(data, pos + 4)?);
+            pos += 8;
+        }
+        for _ in 0..name_count {
+            let raw = data
+                .get(pos..pos + name_size)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "RSTB truncated"))?;
+            let len = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+            res.names.insert(String::from_utf8_lossy(&raw[..len]).to_string(), read_u32(data, pos + name_size)?);
+            pos += name_size + 4;
+        }
+        Ok(res)
+    }
+
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(
+            RESTBL_HEADER_SIZE + self.hashes.len() * 8 + self.names.len() * (RESTBL_NAME_SIZE + 4),
+        );
+        res.extend_from_slice(RESTBL_MAGIC);
+        res.extend_from_slice(&1u32.to_le_bytes());
+        res.extend_from_slice(&(RESTBL_NAME_SIZE as u32).to_le_bytes());
+        res.extend_from_slice(&(self.hashes.len() as u32).to_le_bytes());
+        res.extend_from_slice(&(self.names.len() as u32).to_le_bytes());
+        for (hash, size) in self.hashes.iter() {
+            res.extend_from_slice(&hash.to_le_bytes());
+            res.extend_from_slice(&size.to_le_bytes());
+        }
+        for (name, size) in self.names.iter() {
+            let mut raw = name.as_bytes().to_vec();
+            raw.resize(RESTBL_NAME_SIZE, 0);
+            res.extend_from_slice(&raw);
+            res.extend_from_slice(&size.to_le_bytes());
+        }
+        res
+    }
+
+    pub fn from_table(table: &ResourceSizeTable) -> io::Result<RstbEntries> {
+        RstbEntries::from_binary(&table.to_binary())
+    }
+
+    pub fn to_table(&self) -> io::Result<ResourceSizeTable> {
+        let reader = ResTblReader::new(self.to_binary())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+        Ok(ResourceSizeTable::from_parser(&reader))
+    }
+
+    // Names for the hash entries from the known paths: (path -> size, hashes nobody matched)
+    pub fn resolve(&self, known_names: &[String]) -> (BTreeMap<String, u32>, BTreeMap<u32, u32>) {
+        let mut named = self.names.clone();
+        let mut resolved: HashSet<u32> = HashSet::new();
+        for name in known_names.iter() {
+            if named.contains_key(name) {
+                continue;
+            }
+            let hash = rstb_crc32(name);
+            if let Some(size) = self.hashes.get(&hash) {
+                //colliding paths: the first one takes the hash entry, the others are in the name entries
+                if resolved.insert(hash) {
+                    named.insert(name.clone(), *size);
+                }
+            }
+        }
+        let unresolved = self
+            .hashes
+            .iter()
+            .filter(|(hash, _)| !resolved.contains(hash))
+            .map(|(hash, size)| (*hash, *size))
+            .collect();
+        (named, unresolved)
+    }
+
+    // Back from named and hash only entries, a path goes to the name entries only when its hash is taken
+    pub fn from_resolved(named: &BTreeMap<String, u32>, unresolved: &BTreeMap<u32, u32>) -> RstbEntries {
+        let mut res = RstbEntries {
+            hashes: unresolved.clone(),
+            names: BTreeMap::new(),
+        };
+        let mut by_hash: HashMap<u32, Vec<(&String, u32)>> = HashMap::new();
+        for (name, size) in named.iter() {
+            by_hash.entry(rstb_crc32(name)).or_default().push((name, *size));
+        }
+        for (hash, entries) in by_hash {
+            for (name, size) in entries {
+                if res.hashes.contains_key(&hash) {
+                    res.names.insert(name.clone(), size);
+                } else {
+                    res.hashes.insert(hash, size);
+                }
+            }
+        }
+        res
+    }
+}
+
+// Text of Restbl::to_text back to (path -> size, hash -> size). Unquoted 0x keys are numbers to the yaml parser
+fn entries_from_text(text: &str) -> io::Result<(BTreeMap<String, u32>, BTreeMap<u32, u32>)> {
+    let invalid = |m: String| io::Error::new(io::ErrorKind::InvalidData, m);
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(text).map_err(|e| invalid(e.to_string()))?;
+    let mut named: BTreeMap<String, u32> = BTreeMap::new();
+    let mut unresolved: BTreeMap<u32, u32> = BTreeMap::new();
+    for (key, val) in mapping.iter() {
+        let size = val
+            .as_u64()
+            .filter(|v| *v <= u32::MAX as u64)
+            .ok_or_else(|| invalid(format!("Invalid size for {:?}: {:?}", key, val)))?
+            as u32;
+        match key {
+            serde_yaml::Value::Number(n) => {
+                let hash = n.as_u64().filter(|v| *v <= u32::MAX as u64);
+                let hash = hash.ok_or_else(|| invalid(format!("Invalid hash: {}", n)))?;
+                unresolved.insert(hash as u32, size);
+            }
+            serde_yaml::Value::String(name) => match name.strip_prefix("0x").and_then(|h| u32::from_str_radix(h, 16).ok()) {
+                Some(hash) => {
+                    unresolved.insert(hash, size);
+                }
+                None => {
+                    if name.len() >= RESTBL_NAME_SIZE {
+                        return Err(invalid(format!("Path too long for RSTB: {}", name)));
+                    }
+                    named.insert(name.clone(), size);
+                }
+            },
+            _ => return Err(invalid(format!("Invalid entry: {:?}", key))),
+        }
+    }
+    Ok((named, unresolved))
+}
+
+// What a mod romfs brings to the RSTB, as passed to walk_mod_romfs
+pub enum RstbResource<'d> {
+    File(&'d str),       //path on disk
+    PackEntry(&'d [u8]), //entry data, as stored in the pack
+}
+
+// Visits the files under `folders` of the mod romfs (all of it if empty) by RSTB name: path relative to romfs
+// without .zs. Packs under Pack/ are followed by their entries, named by internal path
+pub fn walk_mod_romfs<F>(mod_romfs_path: &str, folders: &[&str], zstd: Arc<TotkZstd>, mut visit: F)
+where
+    F: FnMut(String, RstbResource),
+{
+    let mod_romfs_path = mod_romfs_path.replace("\\", "/").trim_end_matches('/').to_string();
+    let roots: Vec<PathBuf> = if folders.is_empty() {
+        vec![PathBuf::from(&mod_romfs_path)]
+    } else {
+        folders.iter().map(|f| PathBuf::from(&mod_romfs_path).join(f)).collect()
+    };
+    for root in roots.iter() {
+        if !root.exists() {continue;}
+        for file in list_files_recursively(root) {
+            let mut local_path = file[mod_romfs_path.len().min(file.len())..].to_string();
+            if local_path.starts_with("/") {local_path = local_path[1..].to_string()}
+            if local_path.to_ascii_lowercase().ends_with(".zs") {local_path = local_path[..(local_path.len()-3)].to_string()}
+            let local_path_lower = local_path.to_ascii_lowercase();
+            visit(local_path.clone(), RstbResource::File(&file));
+            if local_path_lower.starts_with("pack/") && (local_path_lower.ends_with(".pack") || local_path_lower.ends_with(".sarc")) {
+                if let Ok(pack) = PackFile::new(&file, zstd.clone()) {
+                    for entry in pack.sarc.files() {
+                        let entry_path = entry.name.unwrap_or_default().to_string();
+                        if !entry_path.is_empty() {
+                            visit(entry_path, RstbResource::PackEntry(entry.data));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct Restbl<'a> {
     pub path: Pathlib,
@@ -36,6 +263,8 @@ pub struct Restbl<'a> {
     pub reader: ResTblReader<'a>,
     pub table: ResourceSizeTable,
     pub hash_table: Vec<String>,
+    pub zs_dict: Option<ZsDicType>,
+    pub names_by_hash: HashMap<u32, Vec<String>>, //hash_table by CRC32
 }
 
 impl<'a> Restbl<'_> {
@@ -70,12 +299,7 @@ impl<'a> Restbl<'_> {
     }
 
     pub fn get_restb_entries<P: AsRef<Path>>(&mut self, path: P) -> io::Result<Vec<String>> {
-        //read from zlib json
-        let json_zlibdata = fs::read("bin/totk_rstb_paths.bin")?;
-        let mut decoder = ZlibDecoder::new(&json_zlibdata[..]);
-        let mut json_str = String::new();
-        decoder.read_to_string(&mut json_str)?;
-        let mut res: Vec<String> = serde_json::from_str(&json_str)?;
+        let mut res: Vec<String> = self.zstd.totk_config.index.rstb_paths()?.to_vec();
         let mut p = PathBuf::from(path.as_ref());
         for _ in 0..3 {
             if !p.pop() {
@@ -85,42 +309,10 @@ impl<'a> Restbl<'_> {
         let mod_romfs_path = p.to_string_lossy().to_string().replace("\\", "/");
         //No point in updating from romfs dump
         if mod_romfs_path == self.zstd.totk_config.romfs {return Ok(res);}
-        let mod_romfs_path_len = mod_romfs_path.len();
         //limit to map files and actors
-        let valid_paths = vec!["Pack/Actor",  "AI", "AS"];
-        for entry in valid_paths.iter() {
-            //no point in calling res.contains() since the check costs more time than
-            //adding redundant local path
-            let valid_path = PathBuf::from(&mod_romfs_path).join(entry);
-            if !valid_path.exists() {continue;}
-            for file in list_files_recursively(&valid_path) {
-                // println!("  {}", &file);
-                let mut local_path = file.replace("\\","/")[mod_romfs_path_len..].to_string();
-                if local_path.starts_with("/") {local_path = local_path[1..].to_string()}
-                let local_path_lower = local_path.to_ascii_lowercase();
-                if local_path.to_ascii_lowercase().ends_with(".zs") {local_path = local_path[..(local_path.len()-3)].to_string()}
-                // if !res.contains(&local_path) {
-                    // println!("Adding custom rstb path: {}", &local_path);
-                // }
-                if entry == &"Pack/Actor" && (local_path_lower.ends_with(".pack") || local_path_lower.ends_with(".sarc")) {
-                    if let Ok(pack) = PackFile::new(&file, self.zstd.clone()) {
-                        for entry in pack.sarc.files() {
-                            let entry_path = entry.name.unwrap_or_default().to_string();
-                            // if !entry_path.is_empty() && !res.contains(&entry_path) {
-                            if !entry_path.is_empty()  {
-                                // println!("Adding custom rstb sarc path: {}", &entry_path);
-                                res.push(entry_path);
-                            }
-                        }
-                    }
-                } else {
-                    
-                    res.push(local_path);
-                }
-                
-            }
-        }
-
+        //no point in calling res.contains() since the check costs more time than
+        //adding redundant local path
+        walk_mod_romfs(&mod_romfs_path, &["Pack/Actor", "AI", "AS"], self.zstd.clone(), |name, _| res.push(name));
         Ok(res)
     }
 
@@ -128,8 +320,11 @@ impl<'a> Restbl<'_> {
         let mut f_handle = File::open(&path).ok()?;
         let mut buffer = Vec::new();
         f_handle.read_to_end(&mut buffer).ok()?;
+        let mut zs_dict = None;
         if !is_restbl(&buffer) {
-            buffer = zstd.decompress_zs(&buffer).ok()?;
+            let (dec_data, dict) = zstd.decompress_detect(&buffer).ok()?;
+            buffer = dec_data;
+            zs_dict = Some(dict);
         }
         if !is_restbl(&buffer) {
             return None; //invalid rstb
@@ -146,9 +341,12 @@ impl<'a> Restbl<'_> {
                     reader: r,
                     table: t,
                     hash_table: Default::default(),
+                    names_by_hash: Default::default(),
+                    zs_dict: zs_dict,
                 };
                 //TODO: check if self function works
                 new_restbl.hash_table = new_restbl.get_restb_entries(&path).unwrap_or_default();
+                new_restbl.names_by_hash = names_by_hash(&new_restbl.hash_table);
                 return Some(new_restbl);
             }
             Err(err) => {
@@ -159,6 +357,99 @@ impl<'a> Restbl<'_> {
         None
     }
 
+    // Whole table as yaml for the text editor: named entries, then the ones known only by hash as hex
+    pub fn to_text(&self) -> io::Result<String> {
+        let (named, unresolved) = RstbEntries::from_table(&self.table)?.resolve(&self.hash_table);
+        let mut res = format!(
+            "# {}: {} named entries, {} known only by CRC32 hash\n",
+            &self.path.name,
+            named.len(),
+            unresolved.len()
+        );
+        for (name, size) in named.iter() {
+            let plain = name.chars().all(|c| c.is_ascii_alphanumeric() || "_./-".contains(c))
+                && name.starts_with(|c: char| c.is_ascii_alphabetic());
+            if plain {
+                res.push_str(&format!("{}: {}\n", name, size));
+            } else {
+                res.push_str(&format!("{}: {}\n", serde_json::to_string(name)?, size));
+            }
+        }
+        for (hash, size) in unresolved.iter() {
+            res.push_str(&format!("0x{:08x}: {}\n", hash, size));
+        }
+        Ok(res)
+    }
+
+    pub fn update_from_text(&mut self, text: &str) -> io::Result<()> {
+        let (named, unresolved) = entries_from_text(text)?;
+        self.table = RstbEntries::from_resolved(&named, &unresolved).to_table()?;
+        let known: HashSet<&String> = self.hash_table.iter().collect();
+        let added: Vec<String> = named.keys().filter(|n| !known.contains(n)).cloned().collect();
+        for name in added {
+            self.add_known_name(name);
+        }
+        Ok(())
+    }
+
+    // Path typed in the editor, searchable from now on
+    pub fn add_known_name(&mut self, name: String) {
+        let names = self.names_by_hash.entry(rstb_crc32(&name)).or_default();
+        if !names.contains(&name) {
+            names.push(name.clone());
+            self.hash_table.push(name);
+        }
+    }
+
+    // Other known paths with the same CRC32
+    pub fn collisions(&self, name: &str) -> Vec<String> {
+        match self.names_by_hash.get(&rstb_crc32(name)) {
+            Some(names) => names.iter().filter(|n| n.as_str() != name).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Entries no known path hashes to, only listed by hash
+    pub fn hash_only_entries(&self) -> io::Result<BTreeMap<u32, u32>> {
+        Ok(RstbEntries::from_table(&self.table)?.resolve(&self.hash_table).1)
+    }
+
+    pub fn get_hash(&self, hash: u32) -> io::Result<Option<u32>> {
+        Ok(RstbEntries::from_table(&self.table)?.hashes.get(&hash).copied())
+    }
+
+    pub fn set_hash(&mut self, hash: u32, size: u32) -> io::Result<Option<u32>> {
+        let mut entries = RstbEntries::from_table(&self.table)?;
+        let old = entries.hashes.insert(hash, size);
+        self.table = entries.to_table()?;
+        Ok(old)
+    }
+
+    pub fn remove_hash(&mut self, hash: u32) -> io::Result<Option<u32>> {
+        let mut entries = RstbEntries::from_table(&self.table)?;
+        let old = entries.hashes.remove(&hash);
+        if old.is_some() {
+            self.table = entries.to_table()?;
+        }
+        Ok(old)
+    }
+
+    // A path colliding with another one already in the table goes to the name entries, so the other keeps its size
+    pub fn set_named(&mut self, name: &str, size: u32) -> io::Result<()> {
+        let mut entries = RstbEntries::from_table(&self.table)?;
+        let hash = rstb_crc32(name);
+        let taken = entries.hashes.contains_key(&hash)
+            && self.collisions(name).iter().any(|n| self.table.get(n.clone()).is_some());
+        if taken || entries.names.contains_key(name) {
+            entries.names.insert(name.to_string(), size);
+        } else {
+            entries.hashes.insert(hash, size);
+        }
+        self.table = entries.to_table()?;
+        self.add_known_name(name.to_string());
+        Ok(())
+    }
+
     pub fn save_default(&mut self) -> io::Result<()> {
         self.save(&self.path.full_path.clone())
     }
@@ -168,10 +459,125 @@ impl<'a> Restbl<'_> {
         let mut f = File::create(&path)?;
         if path.to_lowercase().ends_with(".zs") {
             // buffer = self.zstd.compressor.compress_empty(&buffer)?;
-            buffer = self.zstd.compress_empty(&buffer)?;
+            buffer = self.zstd.compress_file(&buffer, path, TotkFileType::Restbl, self.zs_dict)?;
         }
         f.write_all(&buffer)?;
         Ok(())
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //"plumless" and "buckeroo" share the CRC32 0x4ddb0c25
+    const COLLIDING: [&str; 2] = ["buckeroo", "plumless"];
+
+    fn sample_entries() -> RstbEntries {
+        RstbEntries {
+            hashes: BTreeMap::from([(0x0000_0001, 100), (rstb_crc32("Pack/Actor/Foo.pack"), 4096), (0xffff_ffff, 32)]),
+            names: BTreeMap::from([("plumless".to_string(), 200), ("Pack/Actor/Bar.pack".to_string(), 8192)]),
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let entries = sample_entries();
+        let data = entries.to_binary();
+        assert!(data.starts_with(RESTBL_MAGIC));
+        assert_eq!(data.len(), RESTBL_HEADER_SIZE + 3 * 8 + 2 * (RESTBL_NAME_SIZE + 4));
+        assert_eq!(read_u32(&data, 0x0E).unwrap(), 3);
+        assert_eq!(read_u32(&data, 0x12).unwrap(), 2);
+        assert_eq!(RstbEntries::from_binary(&data).unwrap(), entries);
+        //through the restbl crate and back
+        assert_eq!(RstbEntries::from_table(&entries.to_table().unwrap()).unwrap(), entries);
+        let empty = RstbEntries::default();
+        assert_eq!(RstbEntries::from_binary(&empty.to_binary()).unwrap(), empty);
+    }
+
+    #[test]
+    fn invalid_binary() {
+        let data = sample_entries().to_binary();
+        assert_eq!(RstbEntries::from_binary(b"RSTB").unwrap_err().kind(), io::ErrorKind::InvalidData);
+        for len in [RESTBL_HEADER_SIZE - 1, RESTBL_HEADER_SIZE + 12, data.len() - 1] {
+            assert_eq!(RstbEntries::from_binary(&data[..len]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn collisions_go_to_the_name_entries() {
+        let hash = rstb_crc32(COLLIDING[0]);
+        assert_eq!(hash, rstb_crc32(COLLIDING[1]));
+        let named = BTreeMap::from([
+            (COLLIDING[0].to_string(), 10),
+            (COLLIDING[1].to_string(), 20),
+            ("Pack/Actor/Foo.pack".to_string(), 30),
+        ]);
+        let unresolved = BTreeMap::from([(0x1234_abcd, 40)]);
+
+        //the first colliding path takes the hash entry, the other one is stored by name
+        let entries = RstbEntries::from_resolved(&named, &unresolved);
+        assert_eq!(entries.hashes.len(), 3);
+        assert_eq!(entries.names, BTreeMap::from([(COLLIDING[1].to_string(), 20)]));
+        assert_eq!(entries.hashes.get(&hash), Some(&10));
+        assert_eq!(entries.hashes.get(&rstb_crc32("Pack/Actor/Foo.pack")), Some(&30));
+        assert_eq!(entries.hashes.get(&0x1234_abcd), Some(&40));
+
+        let known: Vec<String> = named.keys().cloned().collect();
+        assert_eq!(entries.resolve(&known), (named.clone(), unresolved.clone()));
+        //without the names only the hashes are left
+        let (_, hashes) = entries.resolve(&[]);
+        assert_eq!(hashes, entries.hashes);
+
+        //a hash only entry keeps its slot, both colliding paths are stored by name
+        let unresolved = BTreeMap::from([(hash, 50)]);
+        let entries = RstbEntries::from_resolved(&named, &unresolved);
+        assert_eq!(entries.hashes.get(&hash), Some(&50));
+        assert_eq!(entries.names.get(COLLIDING[0]), Some(&10));
+        assert_eq!(entries.names.get(COLLIDING[1]), Some(&20));
+    }
+
+    #[test]
+    fn text_entries() {
+        let text = "# comment\n\
+                    Pack/Actor/Foo.pack: 30\n\
+                    \"name: with colon\": 31\n\
+                    0x1234abcd: 40\n\
+                    '0x0000ffff': 41\n";
+        let (named, unresolved) = entries_from_text(text).unwrap();
+        assert_eq!(
+            named,
+            BTreeMap::from([("Pack/Actor/Foo.pack".to_string(), 30), ("name: with colon".to_string(), 31)])
+        );
+        assert_eq!(unresolved, BTreeMap::from([(0x1234_abcd, 40), (0x0000_ffff, 41)]));
+
+        let (named, unresolved) = entries_from_text("{}").unwrap();
+        assert!(named.is_empty() && unresolved.is_empty());
+        for text in ["Foo.pack: -1", "Foo.pack: 4294967296", "Foo.pack: big", "[1]: 1"] {
+            assert_eq!(entries_from_text(text).unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", text);
+        }
+        let long = format!("{}: 1", "a".repeat(RESTBL_NAME_SIZE));
+        assert!(entries_from_text(&long).is_err());
+    }
+
+    #[test]
+    fn raw_hash_entries() {
+        assert_eq!(parse_rstb_hash("0x1234abcd"), Some(0x1234_abcd));
+        assert_eq!(parse_rstb_hash(" 0X1234ABCD "), Some(0x1234_abcd));
+        assert_eq!(parse_rstb_hash("0xff"), Some(0xff));
+        for entry in ["", "0x", "1234abcd", "0x123456789", "0xzz", "Pack/Actor/Foo.pack"] {
+            assert_eq!(parse_rstb_hash(entry), None, "{}", entry);
+        }
+        assert_eq!(parse_rstb_hash(&format!("0x{:08x}", rstb_crc32("plumless"))), Some(0x4ddb_0c25));
+    }
+
+    #[test]
+    fn known_names_by_hash() {
+        let names: Vec<String> = ["plumless", "buckeroo", "plumless", "Pack/Actor/Foo.pack"].iter().map(|n| n.to_string()).collect();
+        let by_hash = names_by_hash(&names);
+        assert_eq!(by_hash.len(), 2);
+        assert_eq!(by_hash[&0x4ddb_0c25], vec!["plumless".to_string(), "buckeroo".to_string()]);
+        assert_eq!(by_hash[&rstb_crc32("Pack/Actor/Foo.pack")], vec!["Pack/Actor/Foo.pack".to_string()]);
+    }
+}
@@ -86,8 +86,7 @@ impl<'a> TagProduct<'a> {
             data = self
                 .byml
                 .zstd
-                .compress_zs(&data)
-                .expect("Failed to compress with zs");
+                .compress_file(&data, &path, TotkFileType::TagProduct, self.byml.file_data.zs_dict)?;
         }
         //f_handle.write_all(&data);
         bytes_to_file(data, &path)?;
@@ -306,6 +305,7 @@ impl<'a> TagProduct<'a> {
                 opened_file.path = pathlib_var.clone();
                 opened_file.endian = Some(roead::Endian::Little);
                 opened_file.file_type = TotkFileType::TagProduct;
+                opened_file.zs_dict = tag.byml.file_data.zs_dict;
                 data.status_text = format!("Opened {}", &pathlib_var.full_path);
                 data.path = pathlib_var;
                 data.text = tag.to_text();
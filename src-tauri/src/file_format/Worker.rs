@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::Settings::{NoWindow, NO_WINDOW_FLAG};
+
+// Long lived converter processes (`totkbits.py serve`, `DotNetWrapper serve`), so converting
+// a file no longer costs an interpreter start-up. Protocol over stdin/stdout, little endian:
+//   request:  u32 command length, command (utf8), u32 payload length, payload
+//   response: u8 status (0 ok, 1 error), u32 payload length, payload
+// Error payload is json: {"command": "...", "type": "...", "message": "..."}
+// Workers must keep their stdout for the protocol only, logs go to stderr.
+
+pub const WORKER_TIMEOUT: Duration = Duration::from_secs(120);
+pub const WORKER_POOL_SIZE: usize = 2; //idle workers kept alive per converter
+const STATUS_OK: u8 = 0;
+const STDERR_TAIL_LEN: usize = 4096;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkerError {
+    #[serde(default)]
+    pub command: String,
+    #[serde(default, rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl WorkerError {
+    pub fn from_payload(command: &str, payload: &[u8]) -> Self {
+        serde_json::from_slice::<WorkerError>(payload).unwrap_or_else(|_| WorkerError {
+            command: command.to_string(),
+            kind: "Unknown".to_string(),
+            message: String::from_utf8_lossy(payload).into_owned(),
+        })
+    }
+
+    pub fn into_io(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Error: {} failed ({}): {}", self.command, self.kind, self.message),
+        )
+    }
+}
+
+fn write_frame<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(data)
+}
+
+fn read_reply<R: Read>(reader: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 5];
+    reader.read_exact(&mut header)?;
+    let size = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    let mut payload = vec![0u8; size];
+    reader.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    replies: Receiver<io::Result<(u8, Vec<u8>)>>,
+    stderr_tail: Arc<Mutex<String>>,
+}
+
+impl Worker {
+    fn spawn(program: &str, args: &[String]) -> io::Result<Worker> {
+        let mut child = Command::new(program)
+            .no_window(NO_WINDOW_FLAG)
+            .args(args)
+            .arg("serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("Unable to start converter {}: {}", program, e)))?;
+        let broken = || io::Error::new(io::ErrorKind::BrokenPipe, "Converter pipes unavailable");
+        let stdin = child.stdin.take().ok_or_else(broken)?;
+        let stdout = child.stdout.take().ok_or_else(broken)?;
+        let stderr = child.stderr.take().ok_or_else(broken)?;
+
+        //replies are read on a separate thread, so the caller can give up after a timeout
+        let (tx, replies) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let reply = read_reply(&mut reader);
+                let is_err = reply.is_err();
+                if tx.send(reply).is_err() || is_err {
+                    break;
+                }
+            }
+        });
+        //stderr has to be drained or the worker blocks once the pipe is full
+        let stderr_tail = Arc::new(Mutex::new(String::new()));
+        let tail = stderr_tail.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stderr);
+            let mut line = Vec::new();
+            while let Ok(n) = reader.read_until(b'\n', &mut line) {
+                if n == 0 {
+                    break;
+                }
+                let text = String::from_utf8_lossy(&line).into_owned();
+                eprint!("[worker] {}", &text);
+                if let Ok(mut tail) = tail.lock() {
+                    tail.push_str(&text);
+                    if tail.len() > STDERR_TAIL_LEN {
+                        let mut cut = tail.len() - STDERR_TAIL_LEN;
+                        while !tail.is_char_boundary(cut) {
+                            cut += 1;
+                        }
+                        tail.drain(..cut);
+                    }
+                }
+                line.clear();
+            }
+        });
+
+        Ok(Worker { child, stdin, replies, stderr_tail })
+    }
+
+    // Outer error: the worker is unusable (crashed, timed out, pipe closed). Inner error: it replied with an error
+    fn call(&mut self, command: &str, payload: &[u8], timeout: Duration) -> io::Result<Result<Vec<u8>, WorkerError>> {
+        write_frame(&mut self.stdin, command.as_bytes())?;
+        write_frame(&mut self.stdin, payload)?;
+        self.stdin.flush()?;
+        match self.replies.recv_timeout(timeout) {
+            Ok(Ok((status, data))) => {
+                if status == STATUS_OK {
+                    Ok(Ok(data))
+                } else {
+                    Ok(Err(WorkerError::from_payload(command, &data)))
+                }
+            }
+            Ok(Err(e)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("Converter exited while running {}: {}\n{}", command, e, self.stderr_tail()),
+            )),
+            Err(RecvTimeoutError::Timeout) => {
+                let _ = self.child.kill();
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("Converter did not answer {} within {} s", command, timeout.as_secs()),
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("Converter exited while running {}\n{}", command, self.stderr_tail()),
+            )),
+        }
+    }
+
+    fn stderr_tail(&self) -> String {
+        self.stderr_tail.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+pub struct WorkerPool {
+    pub program: String,
+    pub args: Vec<String>,
+    pub timeout: Duration,
+    pub size: usize,
+    idle: Mutex<Vec<Worker>>,
+}
+
+static POOLS: OnceLock<Mutex<HashMap<String, Arc<WorkerPool>>>> = OnceLock::new();
+
+impl WorkerPool {
+    pub fn new(program: &str, args: &[String]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.to_vec(),
+            timeout: WORKER_TIMEOUT,
+            size: WORKER_POOL_SIZE,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    // One pool per converter command line, shared by every wrapper in the process
+    pub fn shared(program: &str, args: &[String]) -> Arc<WorkerPool> {
+        let key = format!("{} {}", program, args.join(" "));
+        let pools = POOLS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut pools = pools.lock().unwrap_or_else(|e| e.into_inner());
+        pools
+            .entry(key)
+            .or_insert_with(|| Arc::new(WorkerPool::new(program, args)))
+            .clone()
+    }
+
+    fn take_worker(&self) -> io::Result<Worker> {
+        let worker = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        match worker {
+            Some(worker) => Ok(worker),
+            None => Worker::spawn(&self.program, &self.args),
+        }
+    }
+
+    fn give_back(&self, worker: Worker) {
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < self.size {
+                idle.push(worker);
+            }
+        }
+    }
+
+    pub fn call(&self, command: &str, payload: &[u8]) -> io::Result<Vec<u8>> {
+        self.call_timeout(command, payload, self.timeout)
+    }
+
+    pub fn call_timeout(&self, command: &str, payload: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+        //a crashed worker is replaced and the request retried once, a timed out one is not
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut worker = self.take_worker()?;
+            match worker.call(command, payload, timeout) {
+                Ok(Ok(data)) => {
+                    self.give_back(worker);
+                    return Ok(data);
+                }
+                Ok(Err(e)) => {
+                    self.give_back(worker);
+                    return Err(e.into_io());
+                }
+                Err(e) => {
+                    eprintln!("[-] Converter {} failed: {}", &self.program, e);
+                    if e.kind() == io::ErrorKind::TimedOut || attempt > 1 {
+                        return Err(e);
+                    }
+                    println!("[+] Restarting converter {}", &self.program);
+                }
+            }
+        }
+    }
+
+    // Kills idle workers, next call starts fresh ones
+    pub fn shutdown(&self) {
+        if let Ok(mut idle) = self.idle.lock() {
+            idle.clear();
+        }
+    }
+}
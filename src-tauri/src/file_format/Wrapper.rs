@@ -1,7 +1,12 @@
-use std::io::{self, Write};
-use std::os::windows::process::CommandExt;
-use std::process::{Command, Stdio};
-use crate::Settings::NO_WINDOW_FLAG;
+use std::io;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::Settings::{NoWindow, NO_WINDOW_FLAG};
+use crate::TotkConfig::TotkConfig;
+use super::Worker::WorkerPool;
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct ExeWrapper {
     pub exe: String,
@@ -13,83 +18,26 @@ impl ExeWrapper {
         Self { exe, args }
     }
     pub fn dotnet_new() -> Self {
-        // let exe = PathBuf::from(get_cwd_dir().unwrap_or_default()).join("bin/DotNetWrapper.exe").to_string_lossy().to_string();
+        Self::dotnet_from_config(&TotkConfig::default())
+    }
 
-        Self {
-            exe: "bin/cs/DotNetWrapper.exe".to_string(),
-            args: vec![],
-        }
+    pub fn dotnet_from_config(totk_config: &TotkConfig) -> Self {
+        let mut cmd = totk_config.get_dotnet_wrapper();
+        let exe = cmd.remove(0);
+        Self { exe, args: cmd }
     }
 
-    pub fn binary_to_string(&self, data: &Vec<u8>, fname: String) -> io::Result<String> {
-        // if Path::new(&self.exe).exists() == false {
-        //     println!("ExeWrapper: {} does not exist.", &self.exe);
-        // } else {
-        //     println!("ExeWrapper: {} exists.", &self.exe);
-        // }
-        let mut child = Command::new(&self.exe)
-            .creation_flags(NO_WINDOW_FLAG)
-            .args(&self.args)
-            .arg(&fname)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(data)?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-        if stdout.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stdout));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
+    pub fn pool(&self) -> Arc<WorkerPool> {
+        WorkerPool::shared(&self.exe, &self.args)
+    }
 
-        if output.status.success() {
-            // println!("Script executed successfully: {}.", &fname);
-        } else {
-            eprintln!("Script execution failed.");
-            println!("{}", &stderr);
-            return Err(io::Error::new(io::ErrorKind::Other, "Script execution failed."));
-        }
-        Ok(stdout)
+    pub fn binary_to_string(&self, data: &Vec<u8>, fname: String) -> io::Result<String> {
+        let stdout = self.pool().call(&fname, data)?;
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
     }
 
     pub fn string_to_binary(&self, text_data: &str, fname: String) -> io::Result<Vec<u8>> {
-        let mut child = Command::new(&self.exe)
-            .creation_flags(NO_WINDOW_FLAG)
-            .args(&self.args)
-            .arg(&fname)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(text_data.as_bytes())?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        if output.stdout.starts_with(b"Error") {
-            return Err(io::Error::new(io::ErrorKind::Other, String::from_utf8_lossy(&output.stdout).into_owned()));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
-
-        if output.status.success() {
-            // println!("Script executed successfully.");
-        } else {
-            eprintln!("Script execution failed.");
-            return Err(io::Error::new(io::ErrorKind::Other, "Script execution failed."));
-        }
-        Ok(output.stdout)
+        self.pool().call(&fname, text_data.as_bytes())
     }
 }
 
@@ -102,11 +50,7 @@ pub struct PythonWrapper {
 
 impl Default for PythonWrapper {
     fn default() -> Self {
-        Self {
-            python_exe: "bin/winpython/python-3.11.8.amd64/python.exe".to_string(),
-            python_script: "totkbits.py".to_string(),
-            create_no_window: NO_WINDOW_FLAG,
-        }
+        Self::from_config(&TotkConfig::default())
     }
 }
 
@@ -115,139 +59,102 @@ impl PythonWrapper {
     pub fn new() -> Self {
         Self::default()
     }
-    
-
-    pub fn binary_to_string(&self, data: &Vec<u8>, fname: String) -> io::Result<String> {
-        // env::set_var("PATH", self.newpath.clone());
-        let mut child = Command::new(&self.python_exe)
-            // .current_dir(&self.current_dir)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg(&fname)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(data)?;
-            // For binary data, ensure you're handling errors and using `write_all` to guarantee all data is written.
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-        if stdout.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stdout));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
 
-        if output.status.success() {
-            println!("Script executed successfully: {}.", &fname);
-        } else {
-            // eprintln!("Script execution failed.");
-            eprintln!("Script execution failed. {:#?}\n", output.status);
-            return Err(io::Error::new(io::ErrorKind::Other, "Script execution failed."));
+    pub fn from_config(totk_config: &TotkConfig) -> Self {
+        Self {
+            python_exe: totk_config.get_python_exe(),
+            python_script: totk_config.get_python_script(),
+            create_no_window: NO_WINDOW_FLAG,
         }
-        Ok(stdout)
     }
 
-    pub fn text_to_binary_mult_args(&self, args: &Vec<&Vec<u8>>, fname: String) -> io::Result<Vec<u8>> {
-        // println!("Text to binary: spawning child process");
-        let mut child = Command::new(&self.python_exe)
-            // .current_dir(&self.current_dir)
-            .creation_flags(self.create_no_window)
-            .arg(&self.python_script)
-            .arg(&fname)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        // println!("Text to binary: writing to stdin");
-        for arg in args {
-            if let Some(ref mut stdin) = child.stdin.take() {
-                stdin.write_all(&arg)?;
-            } // Dropping `stdin` here closes the pipe.
-        }
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        if output.stdout.starts_with(b"Error") {
-            return Err(io::Error::new(io::ErrorKind::Other, String::from_utf8_lossy(&output.stdout).into_owned()));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
+    pub fn pool(&self) -> Arc<WorkerPool> {
+        WorkerPool::shared(&self.python_exe, &[self.python_script.clone()])
+    }
 
-        if output.status.success() {
-            println!("Script executed successfully.");
-        } else {
-            eprintln!("Script execution failed.");
-            let e = format!("Script execution failed. Unable to convert ainb text to binary. \n{:#?}\n", output.status);
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                e,
-            ));
-        }
-        Ok(output.stdout)
+    pub fn binary_to_string(&self, data: &Vec<u8>, fname: String) -> io::Result<String> {
+        let stdout = self.pool().call(&fname, data)?;
+        println!("Script executed successfully: {}.", &fname);
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
     }
 
+    // The worker protocol carries one payload per call, so only the first argument is sent (as before)
+    pub fn text_to_binary_mult_args(&self, args: &Vec<&Vec<u8>>, fname: String) -> io::Result<Vec<u8>> {
+        let payload: &[u8] = args.first().map(|arg| arg.as_slice()).unwrap_or_default();
+        self.pool().call(&fname, payload)
+    }
 
     pub fn text_to_binary(&self, text_data: &Vec<u8>, fname: String) -> io::Result<Vec<u8>> {
-        // println!("Text to binary: spawning child process");
-        let mut child = Command::new(&self.python_exe)
-            // .current_dir(&self.current_dir)
-            .creation_flags(self.create_no_window)
+        self.pool().call(&fname, text_data)
+    }
+    
+    // Runs the script once without arguments, it answers "Hello from python"
+    pub fn test_winpython(&self) -> io::Result<String> {
+        let output = Command::new(&self.python_exe)
             .arg(&self.python_script)
-            .arg(&fname)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        // println!("Text to binary: writing to stdin");
-        if let Some(ref mut stdin) = child.stdin.take() {
-            stdin.write_all(text_data)?;
-        } // Dropping `stdin` here closes the pipe.
-
-        let output = child.wait_with_output()?;
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        if output.stdout.starts_with(b"Error") {
-            return Err(io::Error::new(io::ErrorKind::Other, String::from_utf8_lossy(&output.stdout).into_owned()));
-        }
-        if stderr.to_lowercase().starts_with("error") {
-            return Err(io::Error::new(io::ErrorKind::Other, stderr));
-        }
-
-        if output.status.success() {
-            println!("Script executed successfully.");
-        } else {
-            eprintln!("Script execution failed.");
-            let e = format!("Script execution failed. Unable to convert ainb text to binary. \n{:#?}\n", output.status);
+            .no_window(self.create_no_window)
+            .output()
+            .map_err(|e| io::Error::new(e.kind(), format!("Unable to start {}: {}", &self.python_exe, e)))?;
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        println!("Test response from winpython: {}", text);
+        if !output.status.success() || !text.contains("Hello from python") {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
-                e,
+                format!("{} {} failed ({}):\n{}", &self.python_exe, &self.python_script, output.status, String::from_utf8_lossy(&output.stderr).trim()),
             ));
         }
-        Ok(output.stdout)
+        Ok(text)
     }
-    
-    pub fn test_winpython(&self) -> io::Result<()> {
-        // env::set_var("PATH", self.newpath.clone());
-        let output = Command::new(&self.python_exe)
-            .arg(&self.python_script)
-            .creation_flags(self.create_no_window)
-            // .arg("-V")
-            .output()?;
-        if output.status.success() {
-            println!("Script executed successfully.");
+}
+
+pub struct ToolCheck {
+    pub name: String,
+    pub command: String,
+    pub result: io::Result<String>,
+}
+
+// Health check for the external converters, used by the settings editor and the cli
+pub fn check_external_tools(totk_config: &TotkConfig) -> Vec<ToolCheck> {
+    let mut res = Vec::new();
+
+    let python = PythonWrapper::from_config(totk_config);
+    let result = python.test_winpython().and_then(|_| {
+        //the worker reports modules it failed to import (ainb, asb, evfl...) after "pong"
+        let pong = python.pool().call_timeout("ping", &[], HEALTH_CHECK_TIMEOUT)?;
+        let pong = String::from_utf8_lossy(&pong).into_owned();
+        let missing: Vec<&str> = pong.lines().skip(1).filter(|l| !l.trim().is_empty()).collect();
+        if missing.is_empty() {
+            Ok("OK".to_string())
         } else {
-            eprintln!("Script execution failed. {:#?}\n", output.status);
-        }
-        let text = String::from_utf8_lossy(&output.stdout);
-        // env::set_var("PATH", self.original_path.clone());
-        println!("Test response from winpython: {}", text);
-        Ok(())
-    }
+            Err(io::Error::new(io::ErrorKind::Other, format!("Missing python modules:\n{}", missing.join("\n"))))
+        }
+    });
+    res.push(ToolCheck {
+        name: "Python (AINB, ASB, EVFL, ESETB)".to_string(),
+        command: format!("{} {}", &python.python_exe, &python.python_script),
+        result,
+    });
+
+    let dotnet = ExeWrapper::dotnet_from_config(totk_config);
+    let result = dotnet
+        .pool()
+        .call_timeout("ping", &[], HEALTH_CHECK_TIMEOUT)
+        .map(|_| "OK".to_string());
+    res.push(ToolCheck {
+        name: "DotNet wrapper (EVFL, PTCL)".to_string(),
+        command: std::iter::once(dotnet.exe.clone()).chain(dotnet.args.clone()).collect::<Vec<_>>().join(" "),
+        result,
+    });
+    res
+}
+
+pub fn tools_report(checks: &[ToolCheck]) -> String {
+    checks
+        .iter()
+        .map(|c| match &c.result {
+            Ok(msg) => format!("[+] {}: {}\n    {}", c.name, msg, c.command),
+            Err(e) => format!("[-] {}: {}\n    {}", c.name, e, c.command),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
\ No newline at end of file
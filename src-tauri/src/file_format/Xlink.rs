@@ -1,6 +1,6 @@
 use std::{ffi::CStr, io, path::Path, sync::Arc};
 
-use libloading::{Library, Symbol};
+use libloading::Symbol;
 use roead::Endian;
 
 use crate::{file_format::BinTextFile::OpenedFile, Open_and_Save::SendData, Settings::Pathlib, Zstd::{is_xlink, TotkFileType, TotkZstd}};
@@ -36,16 +36,11 @@ impl<'a> Xlink_rs<'_> {
                 "Xlink_rs: Not a valid xlink binary",
             ));
         }
-        let xlink_binary_to_yaml: Symbol<XlinkBinaryToYaml> = self
-            .zstd
-            .dll_manager
-            .xlink_dll
-            .get_function("xlink_binary_to_yaml")?;
-        let free_xlink_string: Symbol<FreeXlinkString> = self
-            .zstd
-            .dll_manager
-            .xlink_dll
-            .get_function("free_xlink_string")?;
+        let xlink_dll = self.zstd.plugins.xlink()?;
+        let xlink_binary_to_yaml: Symbol<XlinkBinaryToYaml> =
+            xlink_dll.get_function("xlink_binary_to_yaml")?;
+        let free_xlink_string: Symbol<FreeXlinkString> =
+            xlink_dll.get_function("free_xlink_string")?;
         let c_binary = rawdata.as_ptr() as *const i8;
         unsafe {
             let yaml_ptr = (xlink_binary_to_yaml)(c_binary, rawdata.len());
@@ -64,16 +59,11 @@ impl<'a> Xlink_rs<'_> {
     }
 
     pub fn yaml_to_binary(&self, data: &str) -> io::Result<Vec<u8>> {
-        let xlink_yaml_to_binary: Symbol<XlinkYamlToBinary> = self
-            .zstd
-            .dll_manager
-            .xlink_dll
-            .get_function("xlink_yaml_to_binary")?;
-        let free_xlink_binary: Symbol<FreeXlinkBinary> = self
-            .zstd
-            .dll_manager
-            .xlink_dll
-            .get_function("free_xlink_binary")?;
+        let xlink_dll = self.zstd.plugins.xlink()?;
+        let xlink_yaml_to_binary: Symbol<XlinkYamlToBinary> =
+            xlink_dll.get_function("xlink_yaml_to_binary")?;
+        let free_xlink_binary: Symbol<FreeXlinkBinary> =
+            xlink_dll.get_function("free_xlink_binary")?;
         let rawdata = data.as_bytes();
         // let c_binary = rawdata.as_ptr() as *const i8;
         let mut out_size: usize = 0;
@@ -101,7 +91,7 @@ impl<'a> Xlink_rs<'_> {
     pub fn open_xlink<P:AsRef<Path>>(path: P, zstd: Arc<TotkZstd>)  -> Option<(OpenedFile<'static>, SendData)> {
         let path = path.as_ref();
         let pathlib_var = Pathlib::new(&path);
-        let rawdata = std::fs::read(path).ok()?;
+        let (rawdata, zs_dict) = zstd.decompress_if_zstd(std::fs::read(path).ok()?).ok()?;
         let xlink = Xlink_rs::new(zstd).ok()?;
         let mut opened_file = OpenedFile::default();
         let mut data = SendData::default();
@@ -112,6 +102,7 @@ impl<'a> Xlink_rs<'_> {
                 opened_file.path = pathlib_var.clone();
                 opened_file.endian = Some(Endian::Little);
                 opened_file.file_type = TotkFileType::Xlink;
+                opened_file.zs_dict = zs_dict;
                 data.status_text = format!("Opened {}", &pathlib_var.full_path);
                 data.path = pathlib_var;
                 data.text = text;
@@ -120,6 +111,12 @@ impl<'a> Xlink_rs<'_> {
             }
             Err(e) => {
                 println!("No\n{:?}", e);
+                //valid xlink, but the native helper is missing or broken: report instead of falling back to other formats
+                if is_xlink(&rawdata) {
+                    data.tab = "ERROR".to_string();
+                    data.status_text = format!("Error: Unable to open {}: {}", &pathlib_var.name, e);
+                    return Some((opened_file, data));
+                }
                 return None;
             }
         }
@@ -11,6 +11,8 @@ pub mod Asb_py;
 pub mod Evfl_py;
 pub mod Esetb;
 pub mod Wrapper;
+pub mod Worker;
 pub mod SMO;
 pub mod Evfl_cs;
 pub mod Xlink;
+pub mod HexDump;
@@ -0,0 +1,21 @@
+#![allow(non_snake_case, non_camel_case_types)]
+// Shared core of Totkbits, used by both the Tauri app (main.rs) and the headless cli (bin/totkbits-cli.rs)
+// No dialogs in here, prompts go through Interaction::UserInteraction
+pub mod Batch;
+pub mod Comparer;
+pub mod Compression;
+pub mod DiffReport;
+pub mod FolderSearch;
+pub mod Interaction;
+pub mod Minimize;
+pub mod Open_and_Save;
+pub mod Plugins;
+pub mod RomfsIndex;
+pub mod RomfsIndexer;
+pub mod RstbCalc;
+pub mod RstbPatch;
+pub mod Settings;
+pub mod TotkApp;
+pub mod TotkConfig;
+pub mod Zstd;
+pub mod file_format;
@@ -2,36 +2,32 @@
 // #![windows_subsystem = "windows"]
 // #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(non_snake_case, non_camel_case_types)]
+#[cfg(windows)]
 use miow::pipe::NamedPipeBuilder;
-use Settings::BACKUP_UPDATER_NAME;
+use totkbits_core::Settings::BACKUP_UPDATER_NAME;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::{fs, process};
 use std::process::Command;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::Duration;
 use std::{env, io, thread};
 use tauri::Manager;
-use Zstd::get_executable_dir;
-mod Comparer;
-mod Open_and_Save;
-mod Settings;
+use totkbits_core::Zstd::get_executable_dir;
+mod Dialogs;
 mod TauriCommands;
-mod TotkApp;
-mod TotkConfig;
-mod Zstd;
-mod file_format;
-use crate::Settings::{get_startup_data, StartupData};
+use totkbits_core::Settings::StartupData;
+use crate::Dialogs::RfdDialogs;
 use crate::TauriCommands::{
     add_click, add_empty_byml_file, add_files_from_dir_recursively, add_to_dir_click,
     clear_search_in_sarc, close_all_opened_files, compare_files, compare_internal_file_with_vanila,
-    edit_config, edit_internal_file, exit_app, extract_internal_file, extract_folder_from_opened_sarc,
+    close_nested_sarc, edit_config, edit_internal_file, exit_app, get_startup_data, extract_internal_file, extract_folder_from_opened_sarc,
     open_dir_dialog, open_file_dialog, open_file_from_path, open_file_struct,
     remove_internal_sarc_file, rename_internal_sarc_file, restart_app, rstb_edit_entry,
-    rstb_get_entries, rstb_remove_entry, save_as_click, save_file_struct, search_in_sarc,check_if_update_needed,update_app,get_toml_config,update_toml_config
+    rstb_get_entries, rstb_remove_entry, save_as_click, save_file_struct, search_in_sarc,check_if_update_needed,update_app,get_toml_config,update_toml_config,check_external_tools,batch_zstd_folder,undo_sarc_op,redo_sarc_op,restore_vanila_sarc_entry,minimize_sarc,minimize_mod_folder,export_pack_diff_report,build_romfs_index,search_in_folder,cancel_folder_search,regenerate_mod_rstb,export_rstb_patch,apply_rstb_patch,rstb_to_text
 };
-use crate::TotkApp::TotkBitsApp;
+use totkbits_core::TotkApp::TotkBitsApp;
 use updater::TotkbitsVersion::TotkbitsVersion;
 
 
@@ -43,9 +39,9 @@ fn main() -> io::Result<()> {
     main_initialization()?;
     // test_case()?;
     // return Ok(());
-    let startup_data = StartupData::new()?.to_json()?;
+    let startup_data = StartupData::new(&RfdDialogs)?.to_json()?;
     // println!("{:?}", startup_data);
-    let app = Mutex::<TotkBitsApp>::default();
+    let app = Mutex::new(TotkBitsApp::new(Arc::new(RfdDialogs)));
     if let Err(err) = tauri::Builder::default()
         .setup(|app_setup| {
             app_setup.manage(startup_data);
@@ -62,6 +58,20 @@ fn main() -> io::Result<()> {
             open_file_struct,
             open_file_from_path,
             edit_internal_file,
+            close_nested_sarc,
+            undo_sarc_op,
+            redo_sarc_op,
+            restore_vanila_sarc_entry,
+            minimize_sarc,
+            minimize_mod_folder,
+            export_pack_diff_report,
+            build_romfs_index,
+            search_in_folder,
+            cancel_folder_search,
+            regenerate_mod_rstb,
+            export_rstb_patch,
+            apply_rstb_patch,
+            rstb_to_text,
             save_file_struct,
             save_as_click,
             add_click,
@@ -84,7 +94,9 @@ fn main() -> io::Result<()> {
             compare_internal_file_with_vanila,
             check_if_update_needed,
             update_app,
-            get_toml_config,update_toml_config
+            get_toml_config,update_toml_config,
+            check_external_tools,
+            batch_zstd_folder
         ])
         .run(tauri::generate_context!())
     {
@@ -97,6 +109,7 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
+#[cfg(windows)]
 fn pipe_worker() {
     thread::spawn(|| {
         let pipe_name = r"//./pipe/tauri_pipe";
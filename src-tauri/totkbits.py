@@ -1,7 +1,9 @@
 import io
 import os
+import struct
 import sys
 import tempfile
+import traceback
 from typing import Dict
 import oead
 import evfl
@@ -10,20 +12,22 @@ os.chdir(CWD) # Set the current working directory to the script's directory
 sys.path.append(os.path.join(CWD, "bin/ainb/ainb"))
 sys.path.append(os.path.join(CWD, "bin/asb"))
 sys.path.append(os.path.join(CWD, "bin/ptcl"))
+# Import errors can't go to stdout right away, in serve mode stdout belongs to the protocol
+IMPORT_ERRORS = []
 
 try:
     import bin.ainb.ainb.ainb as ainb_lib
     # from bin.ainb.ainb.converter import ainb_to_json, json_to_ainb, ainb_to_yaml, yaml_to_ainb
 except ImportError as e:
-    sys.stdout.buffer.write(b"Error Import: 16 " + str(e).encode("utf-8"))
+    IMPORT_ERRORS.append("Error Import: 16 " + str(e))
 try:
     from bin.asb.asb import ASB
 except ImportError as e:
-    sys.stdout.buffer.write(b"Error Import: 20 " + str(e).encode("utf-8"))
+    IMPORT_ERRORS.append("Error Import: 20 " + str(e))
 try:
     from bin.ptcl.ptcl  import ptcl_binary_to_text_lib, ptcl_apply_edits_lib
 except ImportError as e:
-    sys.stdout.buffer.write(b"Error Import: 24 " + str(e).encode("utf-8"))
+    IMPORT_ERRORS.append("Error Import: 24 " + str(e))
 import json
 try:
     import yaml
@@ -151,22 +155,95 @@ def byml_text_to_binary(encoding="utf-8"): # Converts input JSON file to AINB
     except Exception as e:
         sys.stdout.buffer.write(b"Error: " + str(e).encode(encoding))
 
-if __name__ == "__main__":
-    if len(sys.argv) > 1:
-        commands = {
-            "ainb_binary_to_text": ainb_binary_to_text,
-            "ainb_text_to_binary": ainb_text_to_binary,
-            "asb_binary_to_text": asb_binary_to_text,
-            "asb_text_to_binary": asb_text_to_binary,  
-            "byml_text_to_binary": byml_text_to_binary,
-            "ptcl_binary_to_text": ptcl_binary_to_text,
-            "ptcl_text_to_binary": ptcl_text_to_binary
-        }
+COMMANDS = {
+    "ainb_binary_to_text": ainb_binary_to_text,
+    "ainb_text_to_binary": ainb_text_to_binary,
+    "asb_binary_to_text": asb_binary_to_text,
+    "asb_text_to_binary": asb_text_to_binary,  
+    "byml_text_to_binary": byml_text_to_binary,
+    "ptcl_binary_to_text": ptcl_binary_to_text,
+    "ptcl_text_to_binary": ptcl_text_to_binary
+}
+
+# Worker mode ("totkbits.py serve"), one process handles many conversions. Little endian frames:
+#   request:  u32 command length, command, u32 payload length, payload
+#   response: u8 status (0 ok, 1 error), u32 payload length, payload (json {"command", "type", "message"} on error)
+STATUS_OK = 0
+STATUS_ERROR = 1
+
+class _Stream:
+    # Stands in for sys.stdin/sys.stdout while a command runs, so the commands above work unchanged
+    def __init__(self, data=b""):
+        self.buffer = io.BytesIO(data)
+
+    def write(self, text): # stray prints from libraries go to the log, not into the reply
+        sys.__stderr__.write(text)
+
+    def flush(self):
+        pass
+
+def _read_exact(stream, size):
+    data = b""
+    while len(data) < size:
+        chunk = stream.read(size - len(data))
+        if not chunk:
+            return None
+        data += chunk
+    return data
+
+def _read_frame(stream):
+    header = _read_exact(stream, 4)
+    if header is None:
+        return None
+    (size,) = struct.unpack("<I", header)
+    return _read_exact(stream, size) if size else b""
 
+def _error_reply(command, kind, message):
+    return STATUS_ERROR, json.dumps({"command": command, "type": kind, "message": message}).encode("utf-8")
+
+def _handle(command, payload):
+    if command == "ping":
+        return STATUS_OK, "\n".join(["pong"] + IMPORT_ERRORS).encode("utf-8")
+    if command not in COMMANDS:
+        return _error_reply(command, "UnknownCommand", f"Command '{command}' not recognized.")
+    old_stdin, old_stdout = sys.stdin, sys.stdout
+    sys.stdin, sys.stdout = _Stream(payload), _Stream()
+    out = sys.stdout
+    try:
+        COMMANDS[command]()
+    except Exception as e:
+        return _error_reply(command, type(e).__name__, str(e) + "\n" + traceback.format_exc())
+    finally:
+        sys.stdin, sys.stdout = old_stdin, old_stdout
+    result = out.buffer.getvalue()
+    if result.startswith(b"Error"): # commands report their own failures this way
+        return _error_reply(command, "ConversionError", result.decode("utf-8", errors="replace"))
+    return STATUS_OK, result
+
+def serve():
+    stdin, stdout = sys.stdin.buffer, sys.stdout.buffer
+    for e in IMPORT_ERRORS:
+        sys.stderr.write(e + "\n")
+    while True:
+        command = _read_frame(stdin)
+        payload = _read_frame(stdin) if command is not None else None
+        if payload is None: # parent closed the pipe
+            break
+        status, reply = _handle(command.decode("utf-8", errors="replace"), payload)
+        stdout.write(struct.pack("<BI", status, len(reply)))
+        stdout.write(reply)
+        stdout.flush()
+
+if __name__ == "__main__":
+    if len(sys.argv) > 1 and sys.argv[1] == "serve":
+        serve()
+    elif len(sys.argv) > 1:
+        for e in IMPORT_ERRORS:
+            sys.stdout.buffer.write(e.encode("utf-8"))
         # Execute the function based on the command line argument
-        if sys.argv[1] in commands.keys():
+        if sys.argv[1] in COMMANDS.keys():
             # sys.stdout.write(f"Executing command '{sys.argv[1]}'\n")
-            commands[sys.argv[1]]()
+            COMMANDS[sys.argv[1]]()
         else:
             print(f"Command '{sys.argv[1]}' not recognized.")
     else:
@@ -15,6 +15,7 @@ import {MenuBarDisplayWithUpdater, MenuBarDisplay} from "./MenuBar";
 import InitializeEditor from './MonacoEditor';
 import RstbTree from "./RstbTree";
 import { SearchTextInSarcPrompt } from './SearchTextInSarc';
+import { FolderSearchPrompt } from './FolderSearch';
 import { useEditorContext } from './StateManager';
 import { checkIfUpdateNeeded } from './ButtonClicks';
 import  OptionsEditor  from './OptionsEditor';
@@ -142,6 +143,7 @@ function App() {
         isSearchInSarcOpened={isSearchInSarcOpened}
         setIsSearchInSarcOpened={setIsSearchInSarcOpened}>
       </SearchTextInSarcPrompt>
+      <FolderSearchPrompt />
 
       <ButtonsDisplay
         editorRef={editorRef}
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/tauri'; // Import Tauri invoke method
+import { listen } from '@tauri-apps/api/event';
 import { set } from 'lodash';
 import { act } from 'react';
 
@@ -126,7 +127,7 @@ export async function clearSearchInSarcClick(setpaths, setStatusText, setSearchI
 }
 
 
-export async function editInternalSarcFile(fullPath, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent) {
+export async function editInternalSarcFile(fullPath, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent, setpaths) {
   try {
     if (fullPath === null || fullPath === undefined || fullPath === "") {
       setStatusText("Select some file first!");
@@ -148,6 +149,12 @@ export async function editInternalSarcFile(fullPath, setStatusText, setActiveTab
       setStatusText(`Opened file: ${fullPath}`);
       setActiveTab(content.tab);
       
+    } else if (content.tab === 'SARC' && setpaths) {
+      //sarc nested in the opened one
+      setLabelTextDisplay(prevState => ({ ...prevState, sarc: content.file_label.replace(/\/\//g, '/') }));
+      setpaths(content.sarc_paths);
+      setStatusText(content.status_text);
+      setActiveTab(content.tab);
     } else if (content.tab === 'ERROR') {
       console.log("Error opening file, no tab set");
       setStatusText("Unsupported file type");
@@ -161,6 +168,22 @@ export async function editInternalSarcFile(fullPath, setStatusText, setActiveTab
   }
 
 
+}
+export async function closeNestedSarcClick(level, setStatusText, setpaths, setLabelTextDisplay) {
+  try {
+    const content = await invoke('close_nested_sarc', { level: level });
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+    if (content.tab === 'SARC') {
+      setLabelTextDisplay(prevState => ({ ...prevState, sarc: content.file_label.replace(/\/\//g, '/') }));
+      setpaths(content.sarc_paths);
+    }
+  } catch (error) {
+    console.error('Failed to close nested SARC:', error);
+    setStatusText('Error: failed to close nested SARC');
+  }
 }
 export async function OpenFileFromPath(argv1, setStatusText, setActiveTab, setLabelTextDisplay, setpaths, updateEditorContent) {
   try {
@@ -256,6 +279,193 @@ export async function editConfigFileClick(setStatusText) {
 
 }
 
+export async function exportPackDiffReportClick(setStatusText) {
+  try {
+    const content = await invoke('export_pack_diff_report');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+  } catch (error) {
+    console.error("Error invoking 'export_pack_diff_report':", error);
+  }
+}
+
+export async function minimizeSarcClick(setStatusText, setpaths) {
+  try {
+    const content = await invoke('minimize_sarc');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+    if (content.sarc_paths.paths.length > 0) {
+      setpaths(content.sarc_paths);
+    }
+  } catch (error) {
+    console.error("Error invoking 'minimize_sarc':", error);
+  }
+}
+
+export async function minimizeModFolderClick(setStatusText) {
+  try {
+    setStatusText("Comparing with romfs...");
+    const content = await invoke('minimize_mod_folder');
+    if (content === null) {
+      setStatusText("Ready");
+      return;
+    }
+    setStatusText(content.status_text);
+  } catch (error) {
+    console.error("Error invoking 'minimize_mod_folder':", error);
+  }
+}
+
+// Commands running in a background thread report through 'batch-progress' and 'batch-finished' events
+async function invokeWithProgress(command, args, setStatusText) {
+  const unlisteners = [];
+  const stopListening = () => unlisteners.forEach(unlisten => unlisten());
+  let finished = false;
+  try {
+    unlisteners.push(await listen('batch-progress', (event) => {
+      const progress = event.payload;
+      setStatusText(`${progress.done}/${progress.total} ${progress.path}`);
+    }));
+    unlisteners.push(await listen('batch-finished', (event) => {
+      finished = true;
+      setStatusText(event.payload.status_text);
+      stopListening();
+    }));
+    const content = await invoke(command, args);
+    if (content === null) {
+      stopListening(); //cancelled
+      return;
+    }
+    if (!finished) {
+      setStatusText(content.status_text);
+    }
+  } catch (error) {
+    stopListening();
+    console.error(`Error invoking '${command}':`, error);
+  }
+}
+
+export async function regenerateModRstbClick(setStatusText) {
+  try {
+    setStatusText("Estimating RSTB sizes...");
+    const content = await invoke('regenerate_mod_rstb');
+    if (content === null) {
+      setStatusText("RSTB regeneration cancelled");
+      return;
+    }
+    setStatusText(content.status_text);
+  } catch (error) {
+    console.error("Error invoking 'regenerate_mod_rstb':", error);
+  }
+}
+
+export async function rstbToTextClick(setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent) {
+  //whole table in the YAML tab, saving there writes the RSTB
+  try {
+    setStatusText("Converting RSTB to text...");
+    const content = await invoke('rstb_to_text');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+    if (content.tab === 'YAML') {
+      setActiveTab(content.tab);
+      updateEditorContent(content.text, content.lang);
+      setLabelTextDisplay(prevState => ({ ...prevState, yaml: content.file_label.replace(/\/\//g, '/') }));
+    }
+  } catch (error) {
+    console.error("Error invoking 'rstb_to_text':", error);
+  }
+}
+
+export async function exportRstbPatchClick(setStatusText) {
+  try {
+    const content = await invoke('export_rstb_patch');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+  } catch (error) {
+    console.error("Error invoking 'export_rstb_patch':", error);
+  }
+}
+
+export async function applyRstbPatchClick(setStatusText) {
+  //changes the opened RSTB only, it still has to be saved
+  try {
+    const content = await invoke('apply_rstb_patch');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+  } catch (error) {
+    console.error("Error invoking 'apply_rstb_patch':", error);
+  }
+}
+
+export async function batchZstdFolderClick(mode, setStatusText) {
+  //mode: 'compress' or 'decompress', folders are picked on the rust side, the work runs in the background
+  await invokeWithProgress('batch_zstd_folder', { mode: mode }, setStatusText);
+}
+
+export async function buildRomfsIndexClick(setStatusText) {
+  //regenerates the vanilla databases from the romfs dump in config
+  await invokeWithProgress('build_romfs_index', {}, setStatusText);
+}
+
+export async function searchInFolderClick(query, fileTypes, inRomfs, setStatusText, onMatches, onFinished) {
+  //matches come as 'search-matches' events while the search runs in the background
+  const unlisteners = [];
+  const stopListening = () => unlisteners.forEach(unlisten => unlisten());
+  let finished = false;
+  try {
+    unlisteners.push(await listen('search-progress', (event) => {
+      const progress = event.payload;
+      setStatusText(`Searching ${progress.done}/${progress.total} ${progress.path}`);
+    }));
+    unlisteners.push(await listen('search-matches', (event) => {
+      onMatches(event.payload);
+    }));
+    unlisteners.push(await listen('search-finished', (event) => {
+      finished = true;
+      setStatusText(event.payload.status_text);
+      stopListening();
+      onFinished();
+    }));
+    const content = await invoke('search_in_folder', { query: query, fileTypes: fileTypes, inRomfs: inRomfs });
+    if (content === null || content.tab === 'ERROR') {
+      stopListening(); //cancelled or invalid query
+      if (content !== null) {
+        setStatusText(content.status_text);
+      }
+      onFinished();
+      return;
+    }
+    if (!finished) {
+      setStatusText(content.status_text);
+    }
+  } catch (error) {
+    stopListening();
+    onFinished();
+    console.error("Error invoking 'search_in_folder':", error);
+  }
+}
+
+export async function cancelFolderSearchClick(setStatusText) {
+  try {
+    const content = await invoke('cancel_folder_search');
+    if (content !== null) {
+      setStatusText(content.status_text);
+    }
+  } catch (error) {
+    console.error("Error invoking 'cancel_folder_search':", error);
+  }
+}
+
 export async function restartApp(setStatusText) {
   try {
     const content = await invoke('restart_app');
@@ -283,6 +493,38 @@ export async function removeInternalFileClick(internalPath, setStatusText, setpa
   }
 }
 
+export async function restoreVanilaClick(internalPath, setStatusText, setpaths) {
+  try {
+    setStatusText("Restoring...");
+    const content = await invoke('restore_vanila_sarc_entry', { internalPath: internalPath });
+    if (content === null) {
+      setStatusText("No SARC opened");
+      return;
+    }
+    setStatusText(content.status_text);
+    if (content.sarc_paths.paths.length > 0) {
+      setpaths(content.sarc_paths);
+    }
+  } catch (error) {
+    console.error("Error invoking 'restore_vanila_sarc_entry':", error);
+  }
+}
+
+export async function undoSarcOpClick(isRedo, setStatusText, setpaths) {
+  try {
+    const content = await invoke(isRedo ? 'redo_sarc_op' : 'undo_sarc_op');
+    if (content === null) {
+      return;
+    }
+    setStatusText(content.status_text);
+    if (content.sarc_paths.paths.length > 0) {
+      setpaths(content.sarc_paths);
+    }
+  } catch (error) {
+    console.error("Error invoking 'undo_sarc_op':", error);
+  }
+}
+
 export async function replaceInternalFileClick(internalPath, setStatusText, setpaths) {
   try {
     const path = await invoke("open_file_dialog");
@@ -1,5 +1,5 @@
 import React, { useCallback, useEffect, useRef, useState } from 'react';
-import { removeInternalFileClick, replaceInternalFileClick, clearSearchInSarcClick, searchTextInSarcClick, editInternalSarcFile, extractFileClick, fetchAndSetEditorContent, saveAsFileClick, saveFileClick } from './ButtonClicks';
+import { removeInternalFileClick, replaceInternalFileClick, undoSarcOpClick, clearSearchInSarcClick, searchTextInSarcClick, editInternalSarcFile, extractFileClick, fetchAndSetEditorContent, saveAsFileClick, saveFileClick } from './ButtonClicks';
 import { useEditorContext } from './StateManager';
 import { set } from 'lodash';
 
@@ -70,7 +70,7 @@ const ButtonsDisplay = () => {
   };
   const handleOpenInternalSarcFile = () => {
     if (selectedPath.isfile) {
-      editInternalSarcFile(selectedPath.path, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent);
+      editInternalSarcFile(selectedPath.path, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent, setpaths);
     }
   };
 
@@ -133,6 +133,8 @@ const ButtonsDisplay = () => {
     { src: 'save_as.png', alt: 'save_as', onClick: handleSaveAsClick, title: 'Save as' },
     { src: 'edit.png', alt: 'edit', onClick: handleOpenInternalSarcFile, title: 'Edit (Ctrl+E)' },
     { src: 'add_sarc.png', alt: 'add', onClick: handleAddClick, title: 'Add' },
+    { src: 'back.png', alt: 'back', onClick: () => undoSarcOpClick(false, setStatusText, setpaths), title: 'Undo (Ctrl+Z)' },
+    { src: 'forward.png', alt: 'forward', onClick: () => undoSarcOpClick(true, setStatusText, setpaths), title: 'Redo (Ctrl+Shift+Z)' },
     { src: 'extract.png', alt: 'extract', onClick: () => extractFileClick(selectedPath, setStatusText), title: 'Extract' },
     { src: 'lupa.png', alt: 'find', onClick: handleSearchClick, title: 'Search in sarc' },
   ] : activeTab === "YAML" ? [
@@ -174,7 +176,8 @@ const ButtonsDisplay = () => {
             setpaths({
               paths: paths.added_paths,
               added_paths: paths.added_paths,
-              modded_paths: paths.modded_paths
+              modded_paths: paths.modded_paths,
+              breadcrumbs: paths.breadcrumbs
             });
             setStatusText(`Showing only added files (${paths.added_paths.length})`);
             newFilters = { showAll: false, showAdded: true, showModded: false };
@@ -189,7 +192,8 @@ const ButtonsDisplay = () => {
             setpaths({
               paths: paths.modded_paths,
               added_paths: paths.added_paths,
-              modded_paths: paths.modded_paths
+              modded_paths: paths.modded_paths,
+              breadcrumbs: paths.breadcrumbs
             });
             setStatusText(`Showing only modded files (${paths.modded_paths.length})`);
             newFilters = { showAll: false, showAdded: false, showModded: true };
@@ -304,6 +308,19 @@ const ButtonsDisplay = () => {
             extractFileClick(selectedPath, setStatusText);
           }
           break;
+        case 'z': // Ctrl+Z, Ctrl+Shift+Z: the editor has its own history
+        case 'Z':
+          if (activeTabRef.current === 'SARC' && event.target.tagName !== 'INPUT') {
+            event.preventDefault();
+            undoSarcOpClick(event.shiftKey, setStatusText, setpaths);
+          }
+          break;
+        case 'y': // Ctrl+Y
+          if (activeTabRef.current === 'SARC' && event.target.tagName !== 'INPUT') {
+            event.preventDefault();
+            undoSarcOpClick(true, setStatusText, setpaths);
+          }
+          break;
         case 'f': // Ctrl+F: prevent the browser's default action
           event.preventDefault();
           if (activeTabRef.current === 'SARC') {
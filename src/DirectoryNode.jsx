@@ -1,5 +1,5 @@
 import React, { useState } from 'react';
-import { extractRootFolderClick, extractFolderClick, editInternalSarcFile, replaceInternalFileClick, removeInternalFileClick, addInternalFileToDir, extractFileClick, addEmptyByml,addFilesFromDirRecursively } from './ButtonClicks';
+import { extractRootFolderClick, extractFolderClick, editInternalSarcFile, replaceInternalFileClick, removeInternalFileClick, addInternalFileToDir, extractFileClick, addEmptyByml,addFilesFromDirRecursively, restoreVanilaClick } from './ButtonClicks';
 import { useEditorContext } from './StateManager';
 import {compareInternalFileWithOVanila} from './Comparer';
 
@@ -59,10 +59,15 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
   const fullPath = path ? `${path}/${name}` : name;
   // const endian = "LE";
   const isSelected = selected === fullPath;
+  const isRemoved = isFile && (sarcPaths.removed_paths || []).includes(fullPath);
 
   const handleDoubleClick = (e) => {
     e.stopPropagation(); // Prevent the click from bubbling up to parent elements
     console.log(`Double-clicked on directory: ${fullPath}`);
+    if (isRemoved) {
+      setStatusText(`${fullPath} is removed from the opened file`);
+      return;
+    }
     handleOpenInternalSarcFile();
     // Add your custom double-click logic here
   };
@@ -96,7 +101,7 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
   const handleOpenInternalSarcFile = () => {
     closeContextMenu();
     if (isFile) {
-      editInternalSarcFile(fullPath, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent);
+      editInternalSarcFile(fullPath, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent, setpaths);
     }
   };
   const handleCompareInternalSarcFile = () => {
@@ -121,6 +126,10 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
     closeContextMenu();
     addFilesFromDirRecursively(fullPath, setStatusText, setpaths);
   };
+  const handleRestoreVanila = () => {
+    closeContextMenu();
+    restoreVanilaClick(fullPath, setStatusText, setpaths);
+  };
   const handleAddEmptyByml = () => {
     closeContextMenu();
     addEmptyByml(fullPath, setStatusText, setpaths);
@@ -154,7 +163,8 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
     cursor: 'pointer',
     display: 'flex',
     alignItems: 'center',
-    color: isSelected && isFile ? 'white' : 'white',
+    color: isRemoved ? '#808080' : 'white',
+    fontStyle: isRemoved ? 'italic' : 'normal',
     backgroundColor: //isFile ?
       isSelected ?
         sarcPaths.added_paths.includes(fullPath) ? '#2D8589' :
@@ -214,11 +224,16 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
     setContextMenu({ visible: false, x: 0, y: 0 });
   };
 
-  const contextMenuActions = isFile ? [
+  const contextMenuActions = isRemoved ? [
+    { label: 'Restore vanilla', method: handleRestoreVanila, icon: 'context_menu/replace.png', shortcut: '' },
+    { label: 'Copy path', method: () => handlePathToClipboard(fullPath), icon: 'context_menu/copy.png', shortcut: '' },
+    { label: 'Close', method: () => closeContextMenu(), icon: 'context_menu/close.png', shortcut: '' },
+  ] : isFile ? [
     { label: 'Edit', method: handleOpenInternalSarcFile, icon: 'context_menu/edit.png', shortcut: 'F3' },
     { label: 'Compare', method: handleCompareInternalSarcFile, icon: 'context_menu/compare.png', shortcut: '' },
     { label: 'Extract', method: handleExtractInternalSarcFile, icon: 'context_menu/extract.png', shortcut: 'Ctrl+E' },
     { label: 'Replace', method: handleReplaceInternalSarcFile, icon: 'context_menu/replace.png', shortcut: 'Ctrl+R' },
+    { label: 'Restore vanilla', method: handleRestoreVanila, icon: 'context_menu/replace.png', shortcut: '' },
     { label: 'Delete', method: handleRemoveInternalSarcFile, icon: 'context_menu/remove.png', shortcut: '' },
     { label: 'Rename', method: handleRenameInternalSarcFile, icon: 'context_menu/rename.png', shortcut: '' },
     { label: 'Copy path', method: () => handlePathToClipboard(fullPath), icon: 'context_menu/copy.png', shortcut: '' },
@@ -228,6 +243,7 @@ const DirectoryNode = ({ node, name, path, onContextMenu, sarcPaths, selected, o
     { label: 'Add folder', method: handleAddFilesFromDirRecursively, icon: 'context_menu/add_dir.png', shortcut: '' },
     { label: 'Extract', method: handleExtractInternalSarcFolder, icon: 'context_menu/extract.png', shortcut: 'Ctrl+E' },
     { label: 'New byml', method: handleAddEmptyByml, icon: 'context_menu/byml.png', shortcut: '' },
+    { label: 'Restore vanilla', method: handleRestoreVanila, icon: 'context_menu/replace.png', shortcut: '' },
     { label: 'Delete', method: handleRemoveInternalSarcFile, icon: 'context_menu/remove.png', shortcut: '' },
     { label: 'Rename', method: handleRenameInternalSarcFile, icon: 'context_menu/rename.png', shortcut: '' },
     { label: 'Close', method: () => closeContextMenu(), icon: 'context_menu/close.png', shortcut: '' },
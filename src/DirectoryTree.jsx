@@ -1,12 +1,15 @@
 import React, { useState } from 'react';
 import DirectoryNode from './DirectoryNode';
-import { extractFileClick, editInternalSarcFile, fetchAndSetEditorContent, saveAsFileClick, saveFileClick } from './ButtonClicks';
+import { closeNestedSarcClick, extractFileClick, editInternalSarcFile, fetchAndSetEditorContent, saveAsFileClick, saveFileClick } from './ButtonClicks';
+import { useEditorContext } from './StateManager';
 
 const fontsize = '15px';
 
 const buildTree = (paths) => {
   const root = {};
-  paths.paths.forEach((path) => {
+  //removed vanilla entries are listed with the rest, greyed out by DirectoryNode
+  const removed = (paths.removed_paths || []).filter((path) => !paths.paths.includes(path));
+  [...paths.paths, ...removed].forEach((path) => {
     path.split('/').reduce((acc, name, index, arr) => {
       if (!acc[name]) {
         acc[name] = index === arr.length - 1 ? null : {};
@@ -20,7 +23,9 @@ const buildTree = (paths) => {
 const DirectoryTree = ({ onNodeSelect, sarcPaths , setStatusText, activeTab}) => {
   const [selectedNode, setSelectedNode] = useState("");
   const [searchQuery, setSearchQuery] = useState("");
+  const { setpaths, setLabelTextDisplay } = useEditorContext();
   const tree = buildTree(sarcPaths);
+  const breadcrumbs = sarcPaths.breadcrumbs || [];
   // setStatusText("Directory Tree Loaded  ");
   const handleSelectNode = (fullPath, isFile) => {
     setSelectedNode(fullPath,isFile);
@@ -61,6 +66,16 @@ const DirectoryTree = ({ onNodeSelect, sarcPaths , setStatusText, activeTab}) =>
         ...activeTab !== 'SARC' ? { height: '0%', width: '0%', marginLeft: '-50px' } : {}
          }}//robust solution to hide tree when not active. This way collapsed nodes states are not lost
       >
+        {breadcrumbs.length > 1 && <li className='sarc-breadcrumbs'>
+          {breadcrumbs.map((crumb, level) => (
+            <span key={level}>
+              {level > 0 && ' > '}
+              {level < breadcrumbs.length - 1
+                ? <a onClick={() => closeNestedSarcClick(level, setStatusText, setpaths, setLabelTextDisplay)}>{crumb}</a>
+                : <b>{crumb}</b>}
+            </span>
+          ))}
+        </li>}
         {Object.entries(renderTree).map(([key, value]) => (
           <DirectoryNode
             key={key}
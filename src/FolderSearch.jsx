@@ -0,0 +1,105 @@
+import React, { useState } from 'react';
+import { cancelFolderSearchClick, editInternalSarcFile, OpenFileFromPath, searchInFolderClick } from './ButtonClicks';
+import { useEditorContext } from './StateManager';
+
+const MAX_SHOWN_MATCHES = 1000; //all of them are counted in the status bar, the list stays responsive
+
+// Text search in every file and sarc entry of a mod folder or the romfs dump, results are listed while they come
+function FolderSearchPrompt() {
+    const {
+        isFolderSearchOpened, setIsFolderSearchOpened,
+        setStatusText, setActiveTab, setLabelTextDisplay, setpaths, updateEditorContent,
+    } = useEditorContext();
+
+    const [query, setQuery] = useState("");
+    const [fileTypes, setFileTypes] = useState("");
+    const [inRomfs, setInRomfs] = useState(false);
+    const [matches, setMatches] = useState([]);
+    const [isRunning, setIsRunning] = useState(false);
+
+    if (!isFolderSearchOpened) {
+        return null;
+    }
+
+    const handleSearchClick = () => {
+        setMatches([]);
+        setIsRunning(true);
+        searchInFolderClick(query, fileTypes, inRomfs, setStatusText,
+            (found) => setMatches(prev => prev.length >= MAX_SHOWN_MATCHES ? prev : prev.concat(found).slice(0, MAX_SHOWN_MATCHES)),
+            () => setIsRunning(false));
+    };
+
+    const handleStopClick = () => {
+        cancelFolderSearchClick(setStatusText);
+    };
+
+    const closeClick = () => {
+        if (isRunning) {
+            cancelFolderSearchClick(setStatusText);
+        }
+        setIsFolderSearchOpened(false);
+    };
+
+    const openMatch = async (match) => {
+        setIsFolderSearchOpened(false);
+        await OpenFileFromPath(match.full_path, setStatusText, setActiveTab, setLabelTextDisplay, setpaths, updateEditorContent);
+        if (match.internal_path !== "") {
+            editInternalSarcFile(match.internal_path, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent, setpaths);
+        }
+    };
+
+    const canSubmit = query !== "" && !isRunning;
+    const okButtonClass = canSubmit ? "modal-footer-button" : "modal-footer-button-disabled";
+
+    return (
+        <div className="modal-overlay">
+            <div className="modal-content">
+                <button className="close-button" onClick={closeClick}>X</button>
+                <div>Search for text in every file and SARC entry of a folder (NOT case sensitive).</div>
+                <div>File types (optional, comma separated): byml, bcett, aamp, msbt, ainb, asb, tag, evfl, xlink, text</div>
+                <div className="modal-row">
+                    <input
+                        type="text"
+                        placeholder='Actor name, GameData flag...'
+                        className="modal-input"
+                        value={query}
+                        onChange={(e) => setQuery(e.target.value)}
+                        onKeyDown={(e) => { if (e.key === 'Enter' && canSubmit) handleSearchClick(); }}
+                    />
+                </div>
+                <div className="modal-row">
+                    <input
+                        type="text"
+                        placeholder='File types'
+                        className="modal-input"
+                        value={fileTypes}
+                        onChange={(e) => setFileTypes(e.target.value)}
+                    />
+                </div>
+                <div className="modal-row">
+                    <label>
+                        <input type="checkbox" checked={inRomfs} onChange={(e) => setInRomfs(e.target.checked)} />
+                        Search the romfs dump instead of a mod folder
+                    </label>
+                </div>
+                <ul className="folder-search-results">
+                    {matches.map((match, index) => (
+                        <li key={index} className="folder-search-result" title={match.full_path} onClick={() => openMatch(match)}>
+                            <span className="folder-search-path">
+                                {match.internal_path !== "" ? `${match.path} > ${match.internal_path}` : match.path}:{match.line}
+                            </span>
+                            <span className="folder-search-snippet">{match.snippet}</span>
+                        </li>
+                    ))}
+                </ul>
+                <div className="modal-footer">
+                    <button className={okButtonClass} title="Search" disabled={!canSubmit} onClick={handleSearchClick}>Search</button>
+                    {isRunning && <button className="modal-footer-button" title="Stop the search" onClick={handleStopClick}>Stop</button>}
+                    <button className="modal-footer-button" title="Close" onClick={closeClick}>Close</button>
+                </div>
+            </div>
+        </div>
+    );
+}
+
+export { FolderSearchPrompt };
@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/tauri'; // Import Tauri invoke method
 import React, { useEffect, useRef, useState } from "react";
 import "./App.css";
-import { addFilesFromDirRecursivelyToRoot, extractRootFolderClick, clearSearchInSarcClick, closeAllFilesClick, editConfigFileClick, editInternalSarcFile, extractFileClick, fetchAndSetEditorContent, restartApp, saveAsFileClick, saveFileClick, useExitApp } from './ButtonClicks';
+import { addFilesFromDirRecursivelyToRoot, batchZstdFolderClick, buildRomfsIndexClick, regenerateModRstbClick, exportRstbPatchClick, applyRstbPatchClick, rstbToTextClick, exportPackDiffReportClick, minimizeModFolderClick, minimizeSarcClick, extractRootFolderClick, clearSearchInSarcClick, closeAllFilesClick, editConfigFileClick, editInternalSarcFile, extractFileClick, fetchAndSetEditorContent, restartApp, saveAsFileClick, saveFileClick, useExitApp } from './ButtonClicks';
 import { ImageButton } from "./Buttons";
 import { clearCompareData, compareFilesByDecision, compareInternalFileWithOVanila, compareInternalFileWithOVanilaMonaco } from './Comparer';
 import { useEditorContext } from './StateManager';
@@ -13,6 +13,7 @@ function MenuBarDisplay() {
     setIsOptionsOpen, isOptionsOpen,
     searchInSarcQuery, setSearchInSarcQuery, isUpdateNeeded, setIsUpdateNeeded,
     isSearchInSarcOpened, setIsSearchInSarcOpened,
+    setIsFolderSearchOpened,
     renamePromptMessage, setRenamePromptMessage,
     isAddPrompt, setIsAddPrompt,
     activeTab, setActiveTab,
@@ -40,7 +41,7 @@ function MenuBarDisplay() {
     event.stopPropagation(); // Prevent click event from reaching parent
     closeMenu();
     if (activeTab === 'SARC') {
-      editInternalSarcFile(selectedPath.path, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent);
+      editInternalSarcFile(selectedPath.path, setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent, setpaths);
     } else {
       setStatusText("Switch to SARC tab to edit files");
     }
@@ -62,6 +63,12 @@ function MenuBarDisplay() {
     setIsSearchInSarcOpened(!isSearchInSarcOpened);
   }
 
+  const handleFolderSearchClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    setIsFolderSearchOpened(true);
+  }
+
   const handleAddClick = (event) => {
     event.stopPropagation(); // Prevent click event from reaching parent
     closeMenu();
@@ -164,7 +171,7 @@ function MenuBarDisplay() {
     // if (backupPaths.paths.length === 0) {
     //   setBackupPaths(paths);
     // }
-    setpaths({ paths: paths.added_paths, added_paths: paths.added_paths, modded_paths: paths.modded_paths });
+    setpaths({ paths: paths.added_paths, added_paths: paths.added_paths, modded_paths: paths.modded_paths, breadcrumbs: paths.breadcrumbs });
     setStatusText(`Showing only added files (${paths.added_paths.length})`);
   }
 
@@ -174,7 +181,7 @@ function MenuBarDisplay() {
     // if (backupPaths.paths.length === 0) {
     //   setBackupPaths(paths);
     // }
-    setpaths({ paths: paths.modded_paths, added_paths: paths.added_paths, modded_paths: paths.modded_paths });
+    setpaths({ paths: paths.modded_paths, added_paths: paths.added_paths, modded_paths: paths.modded_paths, breadcrumbs: paths.breadcrumbs });
     setStatusText(`Showing only modded files (${paths.modded_paths.length})`);
   }
 
@@ -191,6 +198,60 @@ function MenuBarDisplay() {
     editConfigFileClick(setStatusText);
   }
 
+  const handleMinimizeSarcClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    minimizeSarcClick(setStatusText, setpaths);
+  }
+
+  const handleExportDiffReportClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    exportPackDiffReportClick(setStatusText);
+  }
+
+  const handleMinimizeModFolderClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    minimizeModFolderClick(setStatusText);
+  }
+
+  const handleBatchZstdClick = (mode) => (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    batchZstdFolderClick(mode, setStatusText);
+  }
+
+  const handleRegenerateModRstbClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    regenerateModRstbClick(setStatusText);
+  }
+
+  const handleRstbToTextClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    rstbToTextClick(setStatusText, setActiveTab, setLabelTextDisplay, updateEditorContent);
+  }
+
+  const handleExportRstbPatchClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    exportRstbPatchClick(setStatusText);
+  }
+
+  const handleApplyRstbPatchClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    applyRstbPatchClick(setStatusText);
+  }
+
+  const handleBuildRomfsIndexClick = (event) => {
+    event.stopPropagation(); // Prevent click event from reaching parent
+    closeMenu();
+    buildRomfsIndexClick(setStatusText);
+  }
+
   const restartAppClick = (event) => {
     event.stopPropagation(); // Prevent click event from reaching parent
     closeMenu();
@@ -247,17 +308,28 @@ function MenuBarDisplay() {
     { label: 'Save', onClick: handleSaveClick, icon: 'menu/save.png', shortcut: 'Ctrl+S' },
     { label: 'Save as', onClick: handleSaveAsClick, icon: 'menu/save_as.png', shortcut: 'Ctrl+Shift+S' },
     { label: 'Close all', onClick: handleCloseAllFilesClick, icon: 'menu/closeall.png', shortcut: '' },
+    { label: 'Decompress folder', onClick: handleBatchZstdClick('decompress'), icon: blankIcon, shortcut: '' },
+    { label: 'Compress folder', onClick: handleBatchZstdClick('compress'), icon: blankIcon, shortcut: '' },
+    { label: 'Minimize mod folder', onClick: handleMinimizeModFolderClick, icon: blankIcon, shortcut: '' },
+    { label: 'Regenerate mod RSTB', onClick: handleRegenerateModRstbClick, icon: blankIcon, shortcut: '' },
+    { label: 'Index romfs', onClick: handleBuildRomfsIndexClick, icon: blankIcon, shortcut: '' },
     { label: 'Options', onClick: handleEditOptions, icon: 'menu/edit_config.png', shortcut: '' },
     { label: 'Restart', onClick: restartAppClick, icon: 'menu/restart.png', shortcut: '' },
     { label: 'Exit', onClick: useExitApp, icon: 'menu/exit.png', shortcut: '' }
   ];
   const isSarcOpened = paths.paths.length > 0 && activeTab === "SARC";
+  const isRstbOpened = activeTab === "RSTB" && labelTextDisplay.rstb?.length > 0;
   const isInternalFileSelected = isSarcOpened && selectedPath.path !== '' && selectedPath.isfile;
   const toolsMenuItems = [
     { label: 'Add file', onClick: handleAddClick, icon: 'menu/add.png', shortcut: '', condition: isSarcOpened },
     { label: 'Add folder', onClick: handleAddFolderClick, icon: 'menu/add_folder.png', shortcut: '', condition: isSarcOpened },
     { label: 'Extract sarc contents', onClick: handleExtractOpenedSarc, icon: 'context_menu/extract_all.png', shortcut: '', condition: isSarcOpened },
+    { label: 'Minimize sarc', onClick: handleMinimizeSarcClick, icon: blankIcon, shortcut: '', condition: isSarcOpened },
     { label: 'Search in sarc', onClick: handleSearchClick, icon: 'menu/lupa.png', shortcut: '', condition: isSarcOpened },
+    { label: 'Edit RSTB as text', onClick: handleRstbToTextClick, icon: 'context_menu/edit.png', shortcut: '', condition: isRstbOpened },
+    { label: 'Export RSTB patch', onClick: handleExportRstbPatchClick, icon: blankIcon, shortcut: '', condition: isRstbOpened },
+    { label: 'Apply RSTB patch', onClick: handleApplyRstbPatchClick, icon: blankIcon, shortcut: '', condition: isRstbOpened },
+    { label: 'Search in folder', onClick: handleFolderSearchClick, icon: 'menu/lupa.png', shortcut: '', condition: true },
     { label: 'Clear search', onClick: handleClearSearchTextInSarc, icon: 'menu/clear_search.png', shortcut: '', condition: searchInSarcQuery.length > 0 },
     { label: 'Edit', onClick: handleOpenInternalSarcFile, icon: 'context_menu/edit.png', shortcut: '', condition: isInternalFileSelected },
     { label: 'Extract file', onClick: handleExtractClick, icon: 'context_menu/extract.png', shortcut: '', condition: isInternalFileSelected },
@@ -282,6 +354,7 @@ function MenuBarDisplay() {
     { label: 'Files', onClick: handleCompareFilesFromDisk, icon: blankIcon, shortcut: '', condition: true },
     { label: 'This to file', onClick: handleCompareMonacoEditorFromDisk, icon: blankIcon, shortcut: '', condition: activeTab === "YAML" && labelTextDisplay.yaml?.length > 0 },
     { label: compToVanLabel, onClick: handleCompareFileInternalWithVanila, icon: blankIcon, shortcut: '', condition: selToVanCond },
+    { label: 'Export sarc diff', onClick: handleExportDiffReportClick, icon: blankIcon, shortcut: '', condition: isSarcOpened },
     { label: 'Clear', onClick: handleClearCompareData, icon: blankIcon, shortcut: '', condition: activeTab === "COMPARER" && compareData.content1 !== '' },
 
   ];
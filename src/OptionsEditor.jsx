@@ -56,6 +56,18 @@ function OptionsEditor() {
         setConfig((prev) => ({ ...prev, [key]: value }));
     };
 
+    const handleCheckTools = async () => {
+        try {
+            setStatusText("Checking external converters...");
+            const content = await invoke("check_external_tools", { newConfig: config });
+            if (content) {
+                setStatusText(content.status_text);
+            }
+        } catch (error) {
+            console.error("Error checking external converters:", error);
+        }
+    };
+
     const handleSave = async () => {
         let is_zstd_working = false;
         try {
@@ -140,6 +152,7 @@ function OptionsEditor() {
                     ))}
                 </div>
                 <div className="options-modal-footer">
+                    <button onClick={handleCheckTools}>Check converters</button>
                     <button onClick={handleSave}>Save</button>
                     <button onClick={onClose}>Cancel</button>
                 </div>
@@ -137,7 +137,7 @@ const RstbTree = ({ onNodeSelect, sarcPaths, setStatusText, activeTab }) => {
         <input
         className='inputtext'
           type="text"
-          placeholder="Type at least 3 characters"
+          placeholder="Path or raw hash (0x1234abcd), at least 3 characters"
           value={searchQuery}
           onChange={(e) => setSearchQuery(e.target.value)}
           style={{ width: 'calc(100% - 90px)', padding: '5px', color: 'white', }}
@@ -162,6 +162,10 @@ const RstbTree = ({ onNodeSelect, sarcPaths, setStatusText, activeTab }) => {
             background: selectedNode === node.path ? '#3a3a3a' : '#444444'
           }}>
             <span style={{ flexGrow: 1, textAlign: 'left', maxWidth: '6000px', overflow: "hidden", textOverflow: "ellipsis" }}>{node.path}</span>
+            {node.collisions?.length > 0 && (
+              <span className='rstb-collision' title={`Same CRC32 as: ${node.collisions.join(', ')}`}>collision</span>
+            )}
+            {node.hash !== node.path && <span className='rstb-hash' title="CRC32">{node.hash}</span>}
             <span style={{ marginLeft: '20px', marginRight: 'auto', textAlign: 'right' }}>{node.val}</span>
             {/* <button onClick={() => handleEditRow(node)} style={{ marginLeft: marg }}>Edit</button> */}
             <ImageButton src="edit_rstb.png" onClick={() => handleEditRow(node)} alt="Edit" title="Edit" style={{ marginLeft: marg }} />
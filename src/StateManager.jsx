@@ -14,7 +14,7 @@ export const EditorProvider = ({ children }) => {
   const [renamePromptMessage, setRenamePromptMessage] = useState({ message: "Rename internal SARC file:", path: "" });
   const [selectedPath, setSelectedPath] = useState({ path: "", isfile: false }); //selected path from directory tree
   const [labelTextDisplay, setLabelTextDisplay] = useState({ sarc: '', yaml: '', rstb: '', comparer: '' }); //labeltext display near tabs
-  const [paths, setpaths] = useState({ paths: [], added_paths: [], modded_paths: [] }); //paths structures for directory tree
+  const [paths, setpaths] = useState({ paths: [], added_paths: [], modded_paths: [], removed_paths: [], breadcrumbs: [] }); //paths structures for directory tree
   const [isModalOpen, setIsModalOpen] = useState(false);
   const [isAddPrompt, setIsAddPrompt] = useState(false);
   const [isLoading, setIsLoading] = useState(false);
@@ -37,6 +37,7 @@ export const EditorProvider = ({ children }) => {
 
   const [isSearchInSarcOpened, setIsSearchInSarcOpened] = useState(false);
   const [searchInSarcQuery, setSearchInSarcQuery] = useState("");
+  const [isFolderSearchOpened, setIsFolderSearchOpened] = useState(false);
 
   const [config, setConfig] = useState({});
   const [configLoading, setConfigLoading] = useState(false);
@@ -64,6 +65,7 @@ export const EditorProvider = ({ children }) => {
     settings, setSettings,
     searchInSarcQuery, setSearchInSarcQuery,
     isSearchInSarcOpened, setIsSearchInSarcOpened,
+    isFolderSearchOpened, setIsFolderSearchOpened,
     renamePromptMessage, setRenamePromptMessage,
     isAddPrompt, setIsAddPrompt,
     activeTab, setActiveTab,